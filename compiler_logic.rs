@@ -2,6 +2,8 @@
 pub struct SerializableFstDict {
     pub values: Vec<Vec<Arc<str>>>,
     pub max_key_length: usize,
+    /// 编译键时所使用的 Unicode 规范化形式，加载时用于检测与当前查询路径是否一致
+    pub normalization: crate::normalize::NormalizationForm,
 }
 
 pub fn compile_dictionary(input_path: &Path) -> Result<Vec<u8>> {
@@ -16,12 +18,13 @@ pub fn compile_dictionary(input_path: &Path) -> Result<Vec<u8>> {
         let line = line.with_context(|| "Failed to read line from dictionary")?;
         let parts: Vec<&str> = line.split('\t').collect();
         if parts.len() == 2 {
-            let key = parts[0];
+            // 规范化键，使其与查询路径（`OpenCC::convert` 中的规范化后输入）保持一致
+            let key = crate::normalize::normalize(parts[0]);
             let values: Vec<Arc<str>> = parts[1].split(' ').map(|s| s.into()).collect();
 
             if !key.is_empty() && !values.is_empty() && !values.iter().any(|s| s.is_empty()) {
                 max_key_length = max_key_length.max(key.chars().count());
-                entries.insert(key.to_string(), values);
+                entries.insert(key.into_owned(), values);
             }
         }
     }
@@ -42,6 +45,7 @@ pub fn compile_dictionary(input_path: &Path) -> Result<Vec<u8>> {
     let metadata = SerializableFstDict {
         values: values_vec,
         max_key_length,
+        normalization: crate::normalize::current_form(),
     };
 
     let metadata_bytes = bincode::encode_to_vec(&metadata, config::standard())
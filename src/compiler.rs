@@ -11,3 +11,102 @@ use std::sync::Arc;
 
 // 包含共享的编译逻辑
 include!("../compiler_logic.rs");
+
+/// BMES 状态在 HMM 发射概率表中的固定顺序：Begin / Middle / End / Single，
+/// 与 [`crate::segmentation::hmm`] 中 `State` 枚举的判别值一一对应
+const HMM_NUM_STATES: usize = 4;
+const HMM_STATE_B: usize = 0;
+const HMM_STATE_M: usize = 1;
+const HMM_STATE_E: usize = 2;
+const HMM_STATE_S: usize = 3;
+
+/// 用于序列化 HMM 发射概率表的辅助结构体
+///
+/// 和 `SerializableFstDict` 的思路一致：把体积较大的发射概率表用 zstd 压缩后
+/// 再整体 bincode 编码；发射表本身不需要词典那样的前缀匹配，所以这里把每个
+/// 状态对应的字符查找 FST 的原始字节直接当作普通字段存进同一份 bincode 负载，
+/// 不再像词典文件那样为了支持 mmap 零拷贝而把 FST 字节拆出去单独存放。
+#[derive(Encode, Decode)]
+struct SerializableHmmEmissions {
+    /// zstd 压缩后的 `[Vec<f64>; 4]`，按 B/M/E/S 顺序存放各状态下字符发射对数概率表
+    compressed_probs: Vec<u8>,
+    /// 每个状态下字符到 `compressed_probs` 解压后对应表中下标的 FST 原始字节
+    fst_bytes: [Vec<u8>; HMM_NUM_STATES],
+    /// 字符未出现在对应状态的发射表中时使用的下限对数概率
+    emission_floor: [f64; HMM_NUM_STATES],
+}
+
+/// 从 BMES 训练语料编译 HMM 发射概率表
+///
+/// 语料每行一个词，单字词即为 `S` 状态的一个样本，多字词的首字记作 `B`、
+/// 末字记作 `E`、中间字记作 `M`。按状态统计每个字符的出现次数，加一平滑后
+/// 取对数得到发射概率，再各自为一个状态建一个字符到概率表下标的 FST，
+/// 整体按 `SerializableFstDict` 同款的 zstd + bincode 方式打包成字节数组，
+/// 供 [`crate::segmentation::hmm::HmmModel`] 解码加载。
+pub fn compile_hmm_emissions(corpus: &str) -> Result<Vec<u8>> {
+    let mut counts: [BTreeMap<char, u64>; HMM_NUM_STATES] = std::array::from_fn(|_| BTreeMap::new());
+
+    for line in corpus.lines() {
+        let word = line.trim();
+        if word.is_empty() || word.starts_with('#') {
+            continue;
+        }
+
+        let chars: Vec<char> = word.chars().collect();
+        if chars.len() == 1 {
+            *counts[HMM_STATE_S].entry(chars[0]).or_insert(0) += 1;
+        } else {
+            *counts[HMM_STATE_B].entry(chars[0]).or_insert(0) += 1;
+            for &ch in &chars[1..chars.len() - 1] {
+                *counts[HMM_STATE_M].entry(ch).or_insert(0) += 1;
+            }
+            *counts[HMM_STATE_E]
+                .entry(*chars.last().expect("already checked chars.len() > 1"))
+                .or_insert(0) += 1;
+        }
+    }
+
+    // 加一平滑：未登录字符相当于多出一次计数，避免零概率
+    const ALPHA: f64 = 1.0;
+    let mut probs: [Vec<f64>; HMM_NUM_STATES] = std::array::from_fn(|_| Vec::new());
+    let mut fst_bytes: [Vec<u8>; HMM_NUM_STATES] = std::array::from_fn(|_| Vec::new());
+    let mut emission_floor = [0.0; HMM_NUM_STATES];
+
+    for state in 0..HMM_NUM_STATES {
+        let total: u64 = counts[state].values().sum();
+        let vocab = counts[state].len() as f64;
+        let denom = total as f64 + ALPHA * vocab;
+
+        let mut builder = MapBuilder::memory();
+        let mut state_probs = Vec::with_capacity(counts[state].len());
+        // `BTreeMap<char, _>` 按码点升序迭代，和合法 UTF-8 字符串的字节序一致，
+        // 满足 `MapBuilder` 要求按升序插入键的约束
+        for (index, (&ch, &count)) in counts[state].iter().enumerate() {
+            state_probs.push(((count as f64 + ALPHA) / denom).ln());
+            let mut buf = [0u8; 4];
+            builder
+                .insert(ch.encode_utf8(&mut buf).as_bytes(), index as u64)
+                .with_context(|| "Failed to insert character into HMM emission FST")?;
+        }
+
+        fst_bytes[state] = builder
+            .into_inner()
+            .with_context(|| "Failed to finalize HMM emission FST")?;
+        probs[state] = state_probs;
+        emission_floor[state] = (ALPHA / denom.max(ALPHA)).ln();
+    }
+
+    let probs_bytes = bincode::encode_to_vec(&probs, config::standard())
+        .with_context(|| "Bincode HMM emission table serialization failed")?;
+    let compressed_probs =
+        zstd::encode_all(&probs_bytes[..], 0).with_context(|| "Zstd compression failed")?;
+
+    let metadata = SerializableHmmEmissions {
+        compressed_probs,
+        fst_bytes,
+        emission_floor,
+    };
+
+    bincode::encode_to_vec(&metadata, config::standard())
+        .with_context(|| "Bincode HMM metadata serialization failed")
+}
@@ -16,6 +16,22 @@ pub struct Config {
     /// 转换步骤链
     pub conversion_chain: Vec<ConversionNodeConfig>,
 
+    /// 预分段时，永远不允许被词典跨越匹配的额外分隔符，即使它们本身落在 CJK 范围内。
+    /// 每个字符串中的每一个字符都会被当作一个独立的分隔符。
+    #[serde(default)]
+    pub never_cross_delimiters: String,
+
+    /// 预分段时额外视为 CJK 的字符，即使它们不落在 [`crate::presegment::is_cjk`]
+    /// 内置的范围表里。每个字符串中的每一个字符都会被当作一个独立的字符加入
+    /// 判定；省略时预分段完全按内置范围表判断，行为与引入本字段之前一致。
+    #[serde(default)]
+    pub extra_cjk_chars: String,
+
+    /// 在交给转换链之前先对每个 CJK 片段分词的可选配置。
+    /// 省略时转换链按原来的方式直接对整个片段做贪婪匹配，不经过任何分词器。
+    #[serde(default)]
+    pub segmentation: Option<SegmentationConfig>,
+
     /// 配置文件所在的目录
     #[serde(skip)]
     config_directory: PathBuf,
@@ -107,6 +123,16 @@ pub struct ConversionNodeConfig {
     pub dict: DictConfig,
 }
 
+/// 分词器配置
+#[derive(Deserialize, Debug)]
+pub struct SegmentationConfig {
+    /// 分词器类型，例如 "mm"/"mmseg"、"hmm" 或 "bimm"
+    #[serde(rename = "type")]
+    pub seg_type: String,
+    /// 分词所使用的词典
+    pub dict: DictConfig,
+}
+
 /// 代表一个词典配置，可以是一个单独的词典文件，也可以是一组词典
 #[derive(Deserialize, Debug)]
 pub struct DictConfig {
@@ -1,6 +1,7 @@
 //! 负责处理文本转换的核心逻辑
 
 use crate::config::ConversionNodeConfig;
+use crate::dictionary::dict_group::DictGroup;
 use crate::dictionary::{DictType, Dictionary};
 use crate::error::Result;
 use std::borrow::Cow;
@@ -13,6 +14,20 @@ pub struct ConversionChain {
     dictionaries: Vec<Arc<dyn Dictionary>>,
 }
 
+/// [`ConversionChain::convert_candidates`] 产出的一段转换结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// 命中了词典规则的一段：匹配到的源键，以及该键对应的全部候选目标值
+    Matched {
+        /// 匹配到的源键
+        key: String,
+        /// 该键对应的全部候选目标值，总是非空；OpenCC 词典用它表达有歧义的映射关系
+        candidates: Vec<Arc<str>>,
+    },
+    /// 没有命中任何词典规则、原样保留的一段
+    Unmatched(String),
+}
+
 // 为何不使用分词器?
 //
 // 考虑一个“简体 -> 台湾正体”的转换，其中包含地区用词的替换，例如将“内存”转换为“記憶體”。
@@ -24,70 +39,183 @@ pub struct ConversionChain {
 // 在后续的转换步骤中，程序将无法看到完整的“內存”这个词组，因此 `TWPhrasesIT` 中的 `內存 -> 記憶體` 规则也就无法被匹配到。
 
 impl ConversionChain {
-    /// 从文件加载配置来创建一个新的转换链
-    pub(super) fn from_config(config: &[ConversionNodeConfig], config_dir: &Path) -> Result<Self> {
+    /// 从文件加载配置来创建一个新的转换链。
+    /// `overlay` 会被组合进链中的每一个转换步骤，优先于该步骤的编译词典生效。
+    pub(super) fn from_config(
+        config: &[ConversionNodeConfig],
+        config_dir: &Path,
+        overlay: &Arc<dyn Dictionary>,
+    ) -> Result<Self> {
         let dictionaries = config
             .iter()
-            .map(|node| DictType::from_config(&node.dict, config_dir))
+            .map(|node| {
+                let base = DictType::from_config(&node.dict, config_dir)?;
+                Ok(Self::with_overlay(base, overlay))
+            })
             .collect::<Result<Vec<_>>>()?;
         Ok(Self { dictionaries })
     }
 
-    /// 从嵌入式资源加载配置来创建一个新的转换链
-    pub(super) fn from_config_embedded(config: &[ConversionNodeConfig]) -> Result<Self> {
+    /// 从嵌入式资源加载配置来创建一个新的转换链。
+    /// `overlay` 会被组合进链中的每一个转换步骤，优先于该步骤的编译词典生效。
+    pub(super) fn from_config_embedded(
+        config: &[ConversionNodeConfig],
+        overlay: &Arc<dyn Dictionary>,
+    ) -> Result<Self> {
         let dictionaries = config
             .iter()
             // 调用 DictType 即将创建的嵌入式构造函数
-            .map(|node| DictType::from_config_embedded(&node.dict))
+            .map(|node| {
+                let base = DictType::from_config_embedded(&node.dict)?;
+                Ok(Self::with_overlay(base, overlay))
+            })
             .collect::<Result<Vec<_>>>()?;
         Ok(Self { dictionaries })
     }
 
+    /// 将一个转换步骤的编译词典与用户覆盖层组合成一个 `DictGroup`。
+    /// `DictGroup::match_prefix` 在等长匹配时以遍历到的最后一个为准，
+    /// 因此覆盖层放在最后，使其在键长相同时优先于编译词典生效。
+    fn with_overlay(base: Arc<dyn Dictionary>, overlay: &Arc<dyn Dictionary>) -> Arc<dyn Dictionary> {
+        Arc::new(DictGroup::new(vec![base, overlay.clone()]))
+    }
+
+    /// 仅供测试使用：直接用给定的词典列表构造一个转换链，跳过配置文件解析和加载。
+    #[cfg(test)]
+    pub(super) fn for_test(dictionaries: Vec<Arc<dyn Dictionary>>) -> Self {
+        Self { dictionaries }
+    }
+
+    /// 转换链中所有词典（含用户覆盖层）里最长词条的字符数。
+    /// 流式转换需要据此保留足够的跨块前瞻字符，避免在词典键中间截断输入。
+    pub(super) fn max_key_length(&self) -> usize {
+        self.dictionaries
+            .iter()
+            .map(|dict| dict.max_key_length())
+            .max()
+            .unwrap_or(0)
+    }
+
     /// 对分词后的片段执行转换。
-    /// 每个文本片段都会经过整个词典转换链的处理。
+    /// 每个文本片段都会经过整个词典转换链的处理，每一步都选择第一个候选。
     pub(super) fn convert(&self, text: &str) -> String {
+        self.convert_with_selector(text, &mut |_, _| 0)
+    }
+
+    /// 与 [`ConversionChain::convert`] 相同，但每个词典命中位置的候选改由
+    /// `selector` 选择，而不是总是取第一个候选。
+    ///
+    /// `selector` 接收匹配到的源键与全部候选，返回要采用的候选下标；下标越界时
+    /// 回退到第一个候选。同一个 `selector` 会在链上的每一步、每一次命中时被调用。
+    pub(super) fn convert_with_selector(
+        &self,
+        text: &str,
+        selector: &mut dyn FnMut(&str, &[Arc<str>]) -> usize,
+    ) -> String {
         let mut current_cow = Cow::Borrowed(text);
 
         // 将 Cow 传递给转换链中的每个词典
         for dict in &self.dictionaries {
-            current_cow = Self::apply_dict(current_cow, dict.as_ref());
+            current_cow = Self::apply_dict_with(current_cow, dict.as_ref(), selector);
         }
 
         current_cow.into_owned()
     }
 
-    /// 使用单个词典，通过贪婪替换策略对文本进行一次完整的转换
-    fn apply_dict<'a>(text: Cow<'a, str>, dict: &dyn Dictionary) -> Cow<'a, str> {
+    /// 对分词后的片段执行转换，但只保留链中最后一步的全部候选，而不是折叠为单一结果。
+    /// 前面的步骤仍按默认策略（总是取第一个候选）运行，因为后续步骤需要看到完整的词组
+    /// 才能匹配（参见上面“为何不使用分词器”）。
+    pub(super) fn convert_candidates(&self, text: &str) -> Vec<Segment> {
+        let Some((last, earlier)) = self.dictionaries.split_last() else {
+            return vec![Segment::Unmatched(text.to_string())];
+        };
+
+        let mut current = Cow::Borrowed(text);
+        for dict in earlier {
+            current = Self::apply_dict_with(current, dict.as_ref(), &mut |_, _| 0);
+        }
+
+        Self::segment_candidates(&current, last.as_ref())
+    }
+
+    /// 使用单个词典，通过贪婪匹配把文本切分成命中/未命中片段，保留每次命中的全部候选
+    fn segment_candidates(text: &str, dict: &dyn Dictionary) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        let mut i = 0;
+        let mut unmatched_start: Option<usize> = None;
+
+        while i < text.len() {
+            let remaining_text = &text[i..];
+            match dict.match_prefix(remaining_text) {
+                Some((key, values)) if !values.is_empty() => {
+                    if let Some(start) = unmatched_start.take() {
+                        segments.push(Segment::Unmatched(text[start..i].to_string()));
+                    }
+                    segments.push(Segment::Matched {
+                        key: key.to_string(),
+                        candidates: values.to_vec(),
+                    });
+                    i += key.len();
+                }
+                _ => {
+                    if unmatched_start.is_none() {
+                        unmatched_start = Some(i);
+                    }
+                    let ch_len = remaining_text.chars().next().map_or(1, char::len_utf8);
+                    i += ch_len;
+                }
+            }
+        }
+
+        if let Some(start) = unmatched_start {
+            segments.push(Segment::Unmatched(text[start..].to_string()));
+        }
+
+        segments
+    }
+
+    /// 使用单个词典，通过贪婪替换策略对文本进行一次完整的转换，每次命中都调用
+    /// `selector` 从候选中挑选一个
+    fn apply_dict_with<'a>(
+        text: Cow<'a, str>,
+        dict: &dyn Dictionary,
+        selector: &mut dyn FnMut(&str, &[Arc<str>]) -> usize,
+    ) -> Cow<'a, str> {
         let mut result: Option<String> = None;
         let mut i = 0;
 
         while i < text.len() {
             let remaining_text = &text[i..];
-            if let Some((key, [values_0, ..])) = dict.match_prefix(remaining_text) {
-                // 找到了一个匹配
-                let res_str = result.get_or_insert_with(|| {
-                    // 第一次进行更改时，分配结果字符串，并复制到已经跳过的原始字符串部分
-                    let mut new_string = String::with_capacity(text.len());
-                    new_string.push_str(&text[..i]);
-                    new_string
-                });
-
-                // 追加转换后的值，总是选择第一个候选词
-                res_str.push_str(values_0);
-                i += key.len();
-            } else {
-                // 在这个位置没有找到匹配
-                if let Some(ch) = remaining_text.chars().next() {
-                    if let Some(res_str) = result.as_mut() {
-                        // 如果已经在构建一个字符串，追加这个字符
-                        res_str.push(ch);
+            match dict.match_prefix(remaining_text) {
+                Some((key, values)) if !values.is_empty() => {
+                    // 找到了一个匹配
+                    let res_str = result.get_or_insert_with(|| {
+                        // 第一次进行更改时，分配结果字符串，并复制到已经跳过的原始字符串部分
+                        let mut new_string = String::with_capacity(text.len());
+                        new_string.push_str(&text[..i]);
+                        new_string
+                    });
+
+                    // 追加转换后的值，由 `selector` 决定采用哪个候选
+                    let idx = selector(key, &values);
+                    let idx = if idx < values.len() { idx } else { 0 };
+                    res_str.push_str(&values[idx]);
+                    i += key.len();
+                }
+                _ => {
+                    // 在这个位置没有找到匹配
+                    if let Some(ch) = remaining_text.chars().next() {
+                        if let Some(res_str) = result.as_mut() {
+                            // 如果已经在构建一个字符串，追加这个字符
+                            res_str.push(ch);
+                        }
+                        // 如果没有在构建字符串（result 是 None），我们什么也不做
+                        // 因为我们仍然有效地“借用”着原始的切片
+                        i += ch.len_utf8();
+                    } else {
+                        // 此处理论上不可达，因为有 while i < text.len()
+                        break;
                     }
-                    // 如果没有在构建字符串（result 是 None），我们什么也不做
-                    // 因为我们仍然有效地“借用”着原始的切片
-                    i += ch.len_utf8();
-                } else {
-                    // 此处理论上不可达，因为有 while i < text.len()
-                    break;
                 }
             }
         }
@@ -96,6 +224,11 @@ impl ConversionChain {
         // 我们可以返回原始的、借用的字符串切片。否则，我们返回新创建的 `String`
         result.map(Cow::Owned).unwrap_or(text)
     }
+
+    /// 使用单个词典，通过贪婪替换策略对文本进行一次完整的转换，总是选择第一个候选
+    fn apply_dict<'a>(text: Cow<'a, str>, dict: &dyn Dictionary) -> Cow<'a, str> {
+        Self::apply_dict_with(text, dict, &mut |_, _| 0)
+    }
 }
 
 #[cfg(test)]
@@ -116,18 +249,24 @@ mod tests {
             self.entries.insert(key.to_string(), vec![Arc::from(value)]);
             self.max_key_length = self.max_key_length.max(key.len());
         }
+
+        fn add_entry_candidates(&mut self, key: &str, values: &[&str]) {
+            self.entries
+                .insert(key.to_string(), values.iter().map(|v| Arc::from(*v)).collect());
+            self.max_key_length = self.max_key_length.max(key.len());
+        }
     }
 
     impl Dictionary for MockDict {
-        fn match_prefix<'a, 'b>(&'a self, word: &'b str) -> Option<(&'b str, &'a [Arc<str>])> {
+        fn match_prefix<'b>(&self, word: &'b str) -> Option<(&'b str, Arc<[Arc<str>]>)> {
             let mut longest_match_len = 0;
-            let mut result: Option<(&'b str, &'a [Arc<str>])> = None;
+            let mut result: Option<(&'b str, Arc<[Arc<str>]>)> = None;
 
             // 测试中就简单实现了
             for (key, values) in &self.entries {
                 if word.starts_with(key) && key.len() > longest_match_len {
                     longest_match_len = key.len();
-                    result = Some((&word[..key.len()], values.as_slice()));
+                    result = Some((&word[..key.len()], values.as_slice().into()));
                 }
             }
             result
@@ -182,4 +321,73 @@ mod tests {
         // "项目" -> "項目" (dict1) -> "專案" (dict2)
         assert_eq!(result, "一個專案");
     }
+
+    #[test]
+    fn test_convert_candidates_exposes_all_values() {
+        let mut dict = MockDict::default();
+        dict.add_entry_candidates("干", &["幹", "乾", "干"]);
+        dict.add_entry("一", "一");
+        let chain = ConversionChain {
+            dictionaries: vec![Arc::new(dict)],
+        };
+
+        let segments = chain.convert_candidates("一干净");
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Matched {
+                    key: "一".to_string(),
+                    candidates: vec![Arc::from("一")],
+                },
+                Segment::Matched {
+                    key: "干".to_string(),
+                    candidates: vec![Arc::from("幹"), Arc::from("乾"), Arc::from("干")],
+                },
+                Segment::Unmatched("净".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_presegment_and_chain_convert_quote_style_end_to_end() {
+        // 复刻 `OpenCC::convert` 的分段 + 逐段转换流程（见 src/lib.rs 顶部文档示例），
+        // 验证弯引号“”会和周围的表意文字分到同一个 CJK 片段里，从而让引号风格转换
+        // 规则（“ -> 「，” -> 」）真正有机会命中，而不是被当成透传字符直接跳过
+        let mut dict = MockDict::default();
+        dict.add_entry("“", "「");
+        dict.add_entry("”", "」");
+        dict.add_entry("开放中文转换", "開放中文轉換");
+        dict.add_entry("实现", "實現");
+        let chain = ConversionChain {
+            dictionaries: vec![Arc::new(dict)],
+        };
+
+        let input = "“开放中文转换”是完全由 Rust 实现的。";
+        let mut result = String::new();
+        for segment in crate::presegment::split(input, &[], &[]) {
+            match segment {
+                crate::presegment::Segment::Cjk(text) => result.push_str(&chain.convert(text)),
+                crate::presegment::Segment::Passthrough(text) => result.push_str(text),
+            }
+        }
+
+        assert_eq!(result, "「開放中文轉換」是完全由 Rust 實現的。");
+    }
+
+    #[test]
+    fn test_convert_with_selector_picks_custom_candidate() {
+        let mut dict = MockDict::default();
+        dict.add_entry_candidates("干", &["幹", "乾", "干"]);
+        let dict_arc: Arc<dyn Dictionary> = Arc::new(dict);
+        let chain = ConversionChain {
+            dictionaries: vec![dict_arc],
+        };
+
+        // 默认策略：总是取第一个候选
+        assert_eq!(chain.convert("干"), "幹");
+
+        // 自定义 selector：总是取最后一个候选
+        let result = chain.convert_with_selector("干", &mut |_, candidates| candidates.len() - 1);
+        assert_eq!(result, "干");
+    }
 }
@@ -3,35 +3,79 @@
 //! 如果找不到，则从 `.txt` 文件编译。
 
 use crate::dictionary::Dictionary;
-use crate::error::Result;
+use crate::error::{OpenCCError, Result};
+use crate::normalize::NormalizationForm;
 use bincode::{Decode, Encode, config};
 use fst::Map;
+use memmap2::Mmap;
+use std::fmt;
 use std::fs::File;
 use std::io::{BufReader, Read, Write};
+use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
 
-/// 一个使用 FST 实现的词典。
-/// 包含用于快速查询的 FST 映射、存储实际字符串值的向量，
-/// 以及用于优化的最长键长度。
-#[derive(Debug)]
-pub struct FstDict {
+/// 一个使用 FST 实现的词典，按 `D` 泛化其底层存储。
+/// 默认的 `D = Vec<u8>` 对应把整个 `.ocb` 读进堆内存，保持原有 API 不变；
+/// [`MmapBytes`] 则让 FST 直接引用内存映射的页，加载时零拷贝。
+/// 除了 FST 映射外，还包含存储实际字符串值的向量，以及用于优化的最长键长度。
+pub struct FstDict<D: AsRef<[u8]> = Vec<u8>> {
     /// FST 映射，将键映射到 `values` 向量中的索引
-    map: Map<Vec<u8>>,
-    /// 包含词典中所有不重复的值的向量
-    values: Vec<Vec<Arc<str>>>,
+    map: Map<D>,
+    /// 包含词典中所有不重复的值的向量；用 `Arc<[Arc<str>]>` 存储，
+    /// 这样 `match_prefix` 可以直接克隆一份返回，无需绑定到 `&self` 的生命周期
+    values: Vec<Arc<[Arc<str>]>>,
     /// 词典中最长键的长度
     max_key_length: usize,
 }
 
+impl<D: AsRef<[u8]>> fmt::Debug for FstDict<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FstDict")
+            .field("entries", &self.values.len())
+            .field("max_key_length", &self.max_key_length)
+            .finish()
+    }
+}
+
+/// 一段内存映射文件的只读子区间，实现 `AsRef<[u8]>` 以便直接作为 [`fst::Map`] 的存储后端。
+///
+/// 用 `Arc<Mmap>` 持有映射、用 `range` 标出元数据之后 FST 字节所在的区间，
+/// 这样多个词典（乃至多个进程）可以共享同一段映射页，而不必各自拷贝一份到堆上。
+#[derive(Clone)]
+pub struct MmapBytes {
+    mmap: Arc<Mmap>,
+    range: Range<usize>,
+}
+
+impl AsRef<[u8]> for MmapBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.mmap[self.range.clone()]
+    }
+}
+
 /// 用于序列化词典中非 FST 部分的辅助结构体
 #[derive(Encode, Decode)]
 struct SerializableFstDict {
     values: Vec<Vec<Arc<str>>>,
     max_key_length: usize,
+    /// 编译键时所使用的 Unicode 规范化形式，用于检测与当前查询路径是否一致
+    normalization: NormalizationForm,
 }
 
-impl FstDict {
+/// 判断 `compiled_path` 处的编译缓存相对 `text_path` 处的文本源文件是否仍然有效。
+/// 文本源文件不存在时，缓存就是唯一可用的数据，视为有效。
+fn compiled_cache_is_fresh(text_path: &Path, compiled_path: &Path) -> Result<bool> {
+    let Ok(text_meta) = text_path.metadata() else {
+        return Ok(true);
+    };
+    let Ok(compiled_meta) = compiled_path.metadata() else {
+        return Ok(false);
+    };
+    Ok(compiled_meta.modified()? > text_meta.modified()?)
+}
+
+impl FstDict<Vec<u8>> {
     /// 从给定路径创建一个新的 `FstDict` 实例。
     /// 先从预编译的 `.ocb` 加载，
     /// 没有再从文本文件编译
@@ -39,22 +83,9 @@ impl FstDict {
         let path = path.as_ref();
         let compiled_path = path.with_extension("ocb");
 
-        // 检查是否存在预编译的文件
-        if compiled_path.is_file() {
-            // 如果文本源文件也存在，则检查修改时间，判断缓存是否有效
-            if let Ok(text_meta) = path.metadata() {
-                if let Ok(compiled_meta) = compiled_path.metadata() {
-                    let text_modified = text_meta.modified()?;
-                    let compiled_modified = compiled_meta.modified()?;
-                    if compiled_modified > text_modified {
-                        // 缓存比源文件新，可以使用缓存
-                        return Self::from_ocb_file(&compiled_path);
-                    }
-                }
-            } else {
-                // 源文件不存在，但缓存存在，直接使用缓存
-                return Self::from_ocb_file(&compiled_path);
-            }
+        // 检查是否存在预编译的文件，并且相对文本源文件仍然新鲜
+        if compiled_path.is_file() && compiled_cache_is_fresh(path, &compiled_path)? {
+            return Self::from_ocb_file(&compiled_path);
         }
 
         // 无法使用缓存，则从文本文件编译，并创建新的缓存
@@ -71,11 +102,31 @@ impl FstDict {
         Self::from_reader(reader)
     }
 
+    /// 从给定路径加载词典，缓存命中时走零拷贝的内存映射路径。
+    ///
+    /// 与 [`FstDict::new`] 走相同的缓存有效性判断：缓存新鲜时通过
+    /// [`FstDict::from_ocb_file_mmap`] 加载（`FstDict<MmapBytes>`）；
+    /// 缓存缺失或过期时从文本文件重新编译（`FstDict<Vec<u8>>`）并刷新缓存。
+    /// 两种情况都作为 `Arc<dyn Dictionary>` 返回，调用方不需要关心具体的存储类型。
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Arc<dyn Dictionary>> {
+        let path = path.as_ref();
+        let compiled_path = path.with_extension("ocb");
+
+        if compiled_path.is_file() && compiled_cache_is_fresh(path, &compiled_path)? {
+            return Ok(Arc::new(FstDict::from_ocb_file_mmap(&compiled_path)?));
+        }
+
+        let dict = Self::from_text(path)?;
+        let _ = dict.serialize_to_file(&compiled_path);
+        Ok(Arc::new(dict))
+    }
+
     /// 序列化词典
     pub fn serialize_to_file(&self, path: &Path) -> Result<()> {
         let metadata = SerializableFstDict {
-            values: self.values.clone(),
+            values: self.values.iter().map(|v| v.to_vec()).collect(),
             max_key_length: self.max_key_length,
+            normalization: crate::normalize::current_form(),
         };
         let metadata_bytes = bincode::encode_to_vec(&metadata, config::standard())?;
 
@@ -112,6 +163,14 @@ impl FstDict {
         let (metadata, _): (SerializableFstDict, usize) =
             bincode::decode_from_slice(&metadata_bytes, config::standard())?;
 
+        let current_form = crate::normalize::current_form();
+        if metadata.normalization != current_form {
+            return Err(OpenCCError::NormalizationMismatch {
+                expected: current_form,
+                found: metadata.normalization,
+            });
+        }
+
         let mut fst_bytes = Vec::new();
         reader.read_to_end(&mut fst_bytes)?;
 
@@ -119,15 +178,65 @@ impl FstDict {
 
         Ok(Self {
             map,
-            values: metadata.values,
+            values: metadata.values.into_iter().map(|v| v.into()).collect(),
+            max_key_length: metadata.max_key_length,
+        })
+    }
+}
+
+impl FstDict<MmapBytes> {
+    /// 通过内存映射加载 `.ocb` 文件。
+    ///
+    /// 长度前缀和 bincode 元数据仍按普通方式读取，随后把整个文件映射进地址空间，
+    /// 直接在元数据之后的字节区间上构建 FST，不需要先把 FST 数据拷贝进堆内存。
+    pub fn from_ocb_file_mmap(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)?;
+
+        let mut len_bytes = [0u8; 8];
+        file.read_exact(&mut len_bytes)?;
+        let metadata_len = u64::from_le_bytes(len_bytes) as usize;
+
+        let mut metadata_bytes = vec![0; metadata_len];
+        file.read_exact(&mut metadata_bytes)?;
+
+        let (metadata, _): (SerializableFstDict, usize) =
+            bincode::decode_from_slice(&metadata_bytes, config::standard())?;
+
+        let current_form = crate::normalize::current_form();
+        if metadata.normalization != current_form {
+            return Err(OpenCCError::NormalizationMismatch {
+                expected: current_form,
+                found: metadata.normalization,
+            });
+        }
+
+        // 映射整个文件；FST 字节从长度前缀 + 元数据之后开始，一直到文件末尾
+        let mmap = Arc::new(unsafe { Mmap::map(&file)? });
+        let fst_start = 8 + metadata_len;
+        if fst_start > mmap.len() {
+            return Err(OpenCCError::InvalidConfig(format!(
+                "Truncated .ocb file: {}",
+                path.display()
+            )));
+        }
+        let fst_bytes = MmapBytes {
+            range: fst_start..mmap.len(),
+            mmap,
+        };
+
+        let map = Map::new(fst_bytes)?;
+
+        Ok(Self {
+            map,
+            values: metadata.values.into_iter().map(|v| v.into()).collect(),
             max_key_length: metadata.max_key_length,
         })
     }
 }
 
-impl Dictionary for FstDict {
+impl<D: AsRef<[u8]>> Dictionary for FstDict<D> {
     /// 查找输入字符串在词典中的最长前缀匹配
-    fn match_prefix<'a, 'b>(&'a self, word: &'b str) -> Option<(&'b str, &'a [Arc<str>])> {
+    fn match_prefix<'b>(&self, word: &'b str) -> Option<(&'b str, Arc<[Arc<str>]>)> {
         let fst = self.map.as_fst();
         let mut node = fst.root();
 
@@ -165,7 +274,7 @@ impl Dictionary for FstDict {
             // 使用计算出的索引来获取值
             if let Some(values) = self.values.get(value_index as usize) {
                 let key = &word[..len];
-                return Some((key, values.as_slice()));
+                return Some((key, values.clone()));
             }
         }
 
@@ -248,4 +357,82 @@ mod tests {
         let values_str: Vec<&str> = values.iter().map(|v| v.as_ref()).collect();
         assert_eq!(values_str, ["Hello"]);
     }
+
+    #[test]
+    fn test_mmap_loaded_dict_matches_heap_backed_dict() {
+        let dir = tempdir().unwrap();
+        let dict_content = "一\t一\n一个\t一個\n一个半\t一個半\n世纪\t世紀";
+        let txt_path = create_test_dict_file(&dir, dict_content);
+        let ocb_path = txt_path.with_extension("ocb");
+
+        let heap_dict = FstDict::from_text(&txt_path).unwrap();
+        heap_dict.serialize_to_file(&ocb_path).unwrap();
+
+        let mmap_dict = FstDict::from_ocb_file_mmap(&ocb_path).unwrap();
+
+        for word in ["一个半小时", "世纪之交", "一", "不存在的词"] {
+            assert_eq!(
+                heap_dict.match_prefix(word).map(|(k, v)| (k, v.to_vec())),
+                mmap_dict.match_prefix(word).map(|(k, v)| (k, v.to_vec())),
+                "mismatch for word: {word}"
+            );
+        }
+        assert_eq!(heap_dict.max_key_length(), mmap_dict.max_key_length());
+
+        // `FstDict::open` 同样要能走到 mmap 路径并得到一致的结果
+        let opened = FstDict::open(&txt_path).unwrap();
+        let (key, values) = opened.match_prefix("一个半小时").unwrap();
+        assert_eq!(key, "一个半");
+        let values_str: Vec<&str> = values.iter().map(|v| v.as_ref()).collect();
+        assert_eq!(values_str, ["一個半"]);
+    }
+
+    /// 读取一个由 [`FstDict::serialize_to_file`] 产出的 `.ocb` 文件，把其中的
+    /// `normalization` 字段翻转成另一个取值，再写回磁盘，用来模拟“编译缓存所用的
+    /// 规范化形式与当前运行时不一致”的场景。
+    fn flip_stored_normalization(ocb_path: &Path) {
+        let raw = std::fs::read(ocb_path).unwrap();
+        let metadata_len = u64::from_le_bytes(raw[..8].try_into().unwrap()) as usize;
+        let metadata_bytes = &raw[8..8 + metadata_len];
+        let fst_bytes = &raw[8 + metadata_len..];
+
+        let (mut metadata, _): (SerializableFstDict, usize) =
+            bincode::decode_from_slice(metadata_bytes, config::standard()).unwrap();
+        metadata.normalization = if metadata.normalization == crate::normalize::NormalizationForm::None {
+            crate::normalize::NormalizationForm::Nfc
+        } else {
+            crate::normalize::NormalizationForm::None
+        };
+
+        let new_metadata_bytes = bincode::encode_to_vec(&metadata, config::standard()).unwrap();
+        let mut out = Vec::new();
+        out.write_all(&(new_metadata_bytes.len() as u64).to_le_bytes()).unwrap();
+        out.write_all(&new_metadata_bytes).unwrap();
+        out.write_all(fst_bytes).unwrap();
+        std::fs::write(ocb_path, out).unwrap();
+    }
+
+    #[test]
+    fn test_normalization_mismatch_is_raised_on_both_load_paths() {
+        let dir = tempdir().unwrap();
+        let dict_content = "你好\tHello";
+        let txt_path = create_test_dict_file(&dir, dict_content);
+        let ocb_path = txt_path.with_extension("ocb");
+
+        let dict = FstDict::from_text(&txt_path).unwrap();
+        dict.serialize_to_file(&ocb_path).unwrap();
+        flip_stored_normalization(&ocb_path);
+
+        let heap_err = FstDict::from_ocb_file(&ocb_path).unwrap_err();
+        assert!(
+            matches!(heap_err, OpenCCError::NormalizationMismatch { .. }),
+            "expected NormalizationMismatch, got {heap_err:?}"
+        );
+
+        let mmap_err = FstDict::from_ocb_file_mmap(&ocb_path).unwrap_err();
+        assert!(
+            matches!(mmap_err, OpenCCError::NormalizationMismatch { .. }),
+            "expected NormalizationMismatch, got {mmap_err:?}"
+        );
+    }
 }
@@ -2,6 +2,7 @@
 
 pub mod dict_group;
 pub mod fst_dict;
+pub mod user_dict;
 
 pub mod embedded {
     include!(concat!(env!("OUT_DIR"), "/embedded_map.rs"));
@@ -19,10 +20,14 @@ use std::sync::Arc;
 pub trait Dictionary: Send + Sync + Debug {
     /// 在词典中查找给定单词的最长前缀匹配
     ///
+    /// 值以 `Arc<[Arc<str>]>` 返回而不是借用自 `&self` 的切片，这样运行时可变的
+    /// 词典实现（如 [`user_dict::UserDict`]）也能在不持有内部锁、不泄漏内存的前提下
+    /// 满足这个签名：克隆一次 `Arc` 只是原子计数自增，代价和借用引用相当。
+    ///
     /// # 返回
     ///
     /// 如果找到匹配，返回一个包含 `(匹配到的键, 匹配到的值列表)` 的元组
-    fn match_prefix<'a, 'b>(&'a self, word: &'b str) -> Option<(&'b str, &'a [Arc<str>])>;
+    fn match_prefix<'b>(&self, word: &'b str) -> Option<(&'b str, Arc<[Arc<str>]>)>;
 
     /// 返回词典中的最长键长度，可用于分词算法的优化
     fn max_key_length(&self) -> usize;
@@ -44,8 +49,7 @@ impl DictType {
                     OpenCCError::InvalidConfig("'file' not found for 'text' dict".to_string())
                 })?;
                 let dict_path = find_dict_path(file_name, config_dir)?;
-                let dict = FstDict::new(&dict_path)?;
-                Ok(Arc::new(dict))
+                FstDict::open(&dict_path)
             }
             "group" => {
                 let dict_configs = config.dicts.as_ref().ok_or_else(|| {
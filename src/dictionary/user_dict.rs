@@ -0,0 +1,104 @@
+//! 支持运行时增改的用户词典覆盖层
+//!
+//! 与其它词典不同，`UserDict` 不是从 `.ocb`/文本文件编译而来，而是允许
+//! 调用方在运行时通过 `add_word` 直接添加或覆盖词条（专有名词、行业
+//! 术语等），无需重新编译整个词典。每条词值以 `Arc<[Arc<str>]>` 存储，
+//! `match_prefix` 在持有读锁期间克隆一次 `Arc`（只是原子计数自增）再返回，
+//! 不需要把值绑定到 `&self` 的生命周期，因此覆盖同一个键时旧的值
+//! 会在其最后一个 `Arc` 引用被释放后正常回收，不会永久泄漏。
+
+use crate::dictionary::Dictionary;
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// 一个支持运行时增改的内存词典，可作为编译词典之上的覆盖层使用
+#[derive(Default)]
+pub struct UserDict {
+    entries: RwLock<BTreeMap<String, Arc<[Arc<str>]>>>,
+    max_key_length: AtomicUsize,
+}
+
+impl UserDict {
+    /// 创建一个空的用户词典
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加或覆盖一个词条
+    pub fn add_word(&self, key: impl Into<String>, values: Vec<Arc<str>>) {
+        let key = key.into();
+        self.max_key_length
+            .fetch_max(key.chars().count(), Ordering::Relaxed);
+
+        let values: Arc<[Arc<str>]> = values.into();
+        self.entries.write().unwrap().insert(key, values);
+    }
+}
+
+impl Debug for UserDict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserDict")
+            .field("entries_count", &self.entries.read().unwrap().len())
+            .field(
+                "max_key_length",
+                &self.max_key_length.load(Ordering::Relaxed),
+            )
+            .finish()
+    }
+}
+
+impl Dictionary for UserDict {
+    fn match_prefix<'b>(&self, word: &'b str) -> Option<(&'b str, Arc<[Arc<str>]>)> {
+        let max_len = self.max_key_length.load(Ordering::Relaxed);
+        if max_len == 0 {
+            return None;
+        }
+
+        let entries = self.entries.read().unwrap();
+        let mut last_match: Option<(usize, Arc<[Arc<str>]>)> = None;
+        let mut char_count = 0;
+
+        for (idx, ch) in word.char_indices() {
+            char_count += 1;
+            let end = idx + ch.len_utf8();
+            if let Some(values) = entries.get(&word[..end]) {
+                last_match = Some((end, values.clone()));
+            }
+            if char_count >= max_len {
+                break;
+            }
+        }
+
+        last_match.map(|(len, values)| (&word[..len], values))
+    }
+
+    fn max_key_length(&self) -> usize {
+        self.max_key_length.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_word_overrides_and_matches() {
+        let dict = UserDict::new();
+        dict.add_word("内存", vec![Arc::from("記憶體")]);
+
+        let (key, values) = dict.match_prefix("内存条").unwrap();
+        assert_eq!(key, "内存");
+        let values_str: Vec<&str> = values.iter().map(|v| v.as_ref()).collect();
+        assert_eq!(values_str, ["記憶體"]);
+
+        // 覆盖同一个键
+        dict.add_word("内存", vec![Arc::from("内存")]);
+        let (_, values) = dict.match_prefix("内存条").unwrap();
+        let values_str: Vec<&str> = values.iter().map(|v| v.as_ref()).collect();
+        assert_eq!(values_str, ["内存"]);
+
+        assert!(dict.match_prefix("不存在").is_none());
+    }
+}
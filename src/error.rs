@@ -1,3 +1,4 @@
+use crate::normalize::NormalizationForm;
 use thiserror::Error;
 
 /// `ferrous-opencc` 库的主错误类型。
@@ -42,6 +43,15 @@ pub enum OpenCCError {
     /// 从文本文件编译词典时发生错误
     #[error("Dictionary compile failed")]
     DictCompileError(#[from] anyhow::Error),
+
+    /// 词典编译时使用的 Unicode 规范化形式与当前启用的 feature 不一致
+    #[error(
+        "Dictionary normalization mismatch: expected {expected:?}, but dictionary was compiled with {found:?}"
+    )]
+    NormalizationMismatch {
+        expected: NormalizationForm,
+        found: NormalizationForm,
+    },
 }
 
 /// `ferrous-opencc` 操作的 `Result` 类型别名。
@@ -1,7 +1,8 @@
 //! Ferrous OpenCC 的 FFI 接口。
 
 use crate::OpenCC;
-use std::ffi::{CStr, CString, c_char};
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::io;
 use std::panic::{AssertUnwindSafe, catch_unwind};
 use std::sync::atomic::{AtomicBool, Ordering};
 
@@ -170,6 +171,105 @@ pub unsafe extern "C" fn opencc_convert(
     })
 }
 
+/// 由调用方实现的读取回调：向 `buf` 中最多写入 `buf_len` 字节。
+///
+/// 返回实际写入的字节数；返回 `0` 表示输入已结束；返回负数表示读取出错。
+pub type OpenCCReadFn =
+    unsafe extern "C" fn(user_data: *mut c_void, buf: *mut u8, buf_len: usize) -> isize;
+
+/// 由调用方实现的写入回调：消费 `buf` 中的 `buf_len` 字节。
+///
+/// 返回实际写入的字节数；返回负数表示写入出错。
+pub type OpenCCWriteFn =
+    unsafe extern "C" fn(user_data: *mut c_void, buf: *const u8, buf_len: usize) -> isize;
+
+/// 把一个 C 读取回调及其 `user_data` 适配成 [`std::io::Read`]，用于桥接 [`OpenCC::convert_stream`]。
+struct CallbackReader {
+    read_fn: OpenCCReadFn,
+    user_data: *mut c_void,
+}
+
+impl io::Read for CallbackReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = unsafe { (self.read_fn)(self.user_data, buf.as_mut_ptr(), buf.len()) };
+        if n < 0 {
+            return Err(io::Error::new(io::ErrorKind::Other, "读取回调返回了错误"));
+        }
+        Ok(n as usize)
+    }
+}
+
+/// 把一个 C 写入回调及其 `user_data` 适配成 [`std::io::Write`]，用于桥接 [`OpenCC::convert_stream`]。
+struct CallbackWriter {
+    write_fn: OpenCCWriteFn,
+    user_data: *mut c_void,
+}
+
+impl io::Write for CallbackWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = unsafe { (self.write_fn)(self.user_data, buf.as_ptr(), buf.len()) };
+        if n < 0 {
+            return Err(io::Error::new(io::ErrorKind::Other, "写入回调返回了错误"));
+        }
+        Ok(n as usize)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 以有界内存流式转换，通过调用方提供的读/写回调收发数据，
+/// 使 C 调用方无需像 `opencc_convert` 那样把整个文档都放进内存。
+///
+/// # 参数
+/// - `handle_ptr`: 指向有效 `OpenCCHandle` 实例的指针。
+/// - `read_fn`: 提供 UTF-8 编码输入的读取回调。
+/// - `write_fn`: 接收转换结果的写入回调。
+/// - `user_data`: 原样透传给 `read_fn` 与 `write_fn` 的调用方上下文指针。
+///
+/// # 返回
+/// - `OpenCCResult::Success` 表示整个流已转换完毕。
+/// - `OpenCCResult::InvalidHandle` 表示句柄为空或已被销毁。
+/// - `OpenCCResult::InternalError` 表示输入存在非法 UTF-8、回调返回了错误，或发生了 panic。
+///
+/// # Safety
+/// - `handle_ptr` 必须指向一个有效的、尚未被销毁的 `OpenCCHandle`。
+/// - `read_fn` 与 `write_fn` 必须是有效的函数指针，且可以安全地以任意次数、任意线程调用。
+/// - `user_data` 必须指向调用方保证在本次调用期间一直有效的数据，或者为 `NULL`。
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn opencc_convert_stream(
+    handle_ptr: *const OpenCCHandle,
+    read_fn: OpenCCReadFn,
+    write_fn: OpenCCWriteFn,
+    user_data: *mut c_void,
+) -> OpenCCResult {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        if handle_ptr.is_null() {
+            return OpenCCResult::InvalidHandle;
+        }
+
+        let handle = unsafe { &*handle_ptr };
+        if handle.is_destroyed.load(Ordering::SeqCst) {
+            return OpenCCResult::InvalidHandle;
+        }
+
+        let reader = CallbackReader { read_fn, user_data };
+        let writer = CallbackWriter { write_fn, user_data };
+
+        match handle.instance.convert_stream(reader, writer) {
+            Ok(()) => OpenCCResult::Success,
+            Err(_) => OpenCCResult::InternalError,
+        }
+    }));
+
+    result.unwrap_or_else(|_| {
+        // 没有日志库，只能直接打印了
+        eprintln!("opencc_convert_stream 内部发生 Panic！");
+        OpenCCResult::InternalError
+    })
+}
+
 /// 释放返回的字符串内存。
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn opencc_free_string(s_ptr: *mut c_char) {
@@ -185,3 +285,76 @@ pub unsafe extern "C" fn opencc_free_string(s_ptr: *mut c_char) {
         eprintln!("opencc_free_string 内部发生 Panic！");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read, Write};
+
+    unsafe extern "C" fn read_from_cursor(user_data: *mut c_void, buf: *mut u8, buf_len: usize) -> isize {
+        let cursor = unsafe { &mut *(user_data as *mut Cursor<Vec<u8>>) };
+        let slice = unsafe { std::slice::from_raw_parts_mut(buf, buf_len) };
+        match cursor.read(slice) {
+            Ok(n) => n as isize,
+            Err(_) => -1,
+        }
+    }
+
+    unsafe extern "C" fn always_fails_read(_user_data: *mut c_void, _buf: *mut u8, _buf_len: usize) -> isize {
+        -1
+    }
+
+    unsafe extern "C" fn write_into_vec(user_data: *mut c_void, buf: *const u8, buf_len: usize) -> isize {
+        let out = unsafe { &mut *(user_data as *mut Vec<u8>) };
+        let slice = unsafe { std::slice::from_raw_parts(buf, buf_len) };
+        out.extend_from_slice(slice);
+        buf_len as isize
+    }
+
+    unsafe extern "C" fn always_fails_write(_user_data: *mut c_void, _buf: *const u8, _buf_len: usize) -> isize {
+        -1
+    }
+
+    #[test]
+    fn test_callback_reader_forwards_bytes_until_eof() {
+        let mut source = Cursor::new(b"hello world".to_vec());
+        let mut reader = CallbackReader {
+            read_fn: read_from_cursor,
+            user_data: &mut source as *mut Cursor<Vec<u8>> as *mut c_void,
+        };
+
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, b"hello world");
+    }
+
+    #[test]
+    fn test_callback_reader_surfaces_negative_return_as_io_error() {
+        let mut reader = CallbackReader {
+            read_fn: always_fails_read,
+            user_data: std::ptr::null_mut(),
+        };
+        let mut buf = [0u8; 4];
+        assert!(reader.read(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_callback_writer_forwards_bytes() {
+        let mut sink: Vec<u8> = Vec::new();
+        let mut writer = CallbackWriter {
+            write_fn: write_into_vec,
+            user_data: &mut sink as *mut Vec<u8> as *mut c_void,
+        };
+        writer.write_all(b"hi there").unwrap();
+        assert_eq!(sink, b"hi there");
+    }
+
+    #[test]
+    fn test_callback_writer_surfaces_negative_return_as_io_error() {
+        let mut writer = CallbackWriter {
+            write_fn: always_fails_write,
+            user_data: std::ptr::null_mut(),
+        };
+        assert!(writer.write(b"x").is_err());
+    }
+}
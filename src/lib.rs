@@ -25,11 +25,21 @@ pub mod config;
 pub mod conversion;
 pub mod dictionary;
 pub mod error;
+pub mod normalize;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod presegment;
+pub mod segmentation;
 
 use config::Config;
 use conversion::ConversionChain;
+use dictionary::user_dict::UserDict;
+use dictionary::Dictionary;
 use error::Result;
+use segmentation::{Segmentation, SegmentationType};
+use std::io::{self, Read, Write};
 use std::path::Path;
+use std::sync::Arc;
 
 include!(concat!(env!("OUT_DIR"), "/embedded_map.rs"));
 
@@ -39,6 +49,18 @@ pub struct OpenCC {
     name: String,
     /// 用于执行转换的词典链
     conversion_chain: ConversionChain,
+    /// 运行时用户词典覆盖层，优先于转换链中的编译词典生效
+    user_dict: Arc<UserDict>,
+    /// 预分段时，永远不允许被词典跨越匹配的额外分隔符
+    never_cross_delimiters: Vec<char>,
+    /// 预分段时额外视为 CJK 的字符，用于覆盖 [`presegment::is_cjk`] 内置范围表之外、
+    /// 某个配置特有的边界字符类
+    extra_cjk_chars: Vec<char>,
+    /// 在交给转换链之前先对每个 CJK 片段分词的可选分词器；
+    /// 由配置中的 `segmentation` 节点选择（未配置时为 `None`，行为与之前完全一致）。
+    /// 分词得到的词边界对转换链来说是硬边界，词典匹配不会跨越它，
+    /// 因此换一种分词算法（如 `bimm`）可以改变歧义切分的结果
+    segmenter: Option<Box<dyn Segmentation>>,
 }
 
 impl OpenCC {
@@ -58,11 +80,26 @@ impl OpenCC {
         let config_dir = config.get_config_directory();
 
         // 2. 初始化转换链
-        let conversion_chain = ConversionChain::from_config(&config.conversion_chain, config_dir)?;
+        let user_dict = Arc::new(UserDict::new());
+        let overlay: Arc<dyn Dictionary> = user_dict.clone();
+        let conversion_chain =
+            ConversionChain::from_config(&config.conversion_chain, config_dir, &overlay)?;
+
+        // 3. 按需构建分词器
+        let segmenter = config
+            .segmentation
+            .as_ref()
+            .map(|seg_config| SegmentationType::from_config(seg_config, config_dir))
+            .transpose()?
+            .map(SegmentationType::into_segmenter);
 
         Ok(Self {
             name: config.name,
+            never_cross_delimiters: config.never_cross_delimiters.chars().collect(),
+            extra_cjk_chars: config.extra_cjk_chars.chars().collect(),
             conversion_chain,
+            user_dict,
+            segmenter,
         })
     }
 
@@ -77,14 +114,49 @@ impl OpenCC {
         // 从字符串解析配置
         let config: Config = config_str.parse()?;
 
-        let conversion_chain = ConversionChain::from_config_embedded(&config.conversion_chain)?;
+        let user_dict = Arc::new(UserDict::new());
+        let overlay: Arc<dyn Dictionary> = user_dict.clone();
+        let conversion_chain =
+            ConversionChain::from_config_embedded(&config.conversion_chain, &overlay)?;
+
+        let segmenter = config
+            .segmentation
+            .as_ref()
+            .map(SegmentationType::from_config_embedded)
+            .transpose()?
+            .map(SegmentationType::into_segmenter);
 
         Ok(Self {
             name: config.name,
+            never_cross_delimiters: config.never_cross_delimiters.chars().collect(),
+            extra_cjk_chars: config.extra_cjk_chars.chars().collect(),
             conversion_chain,
+            user_dict,
+            segmenter,
         })
     }
 
+    /// 添加或覆盖一个运行时词条，立即对后续的 `convert` 调用生效，无需重新编译词典。
+    ///
+    /// # 参数
+    ///
+    /// * `key`: 要匹配的源字符串
+    /// * `values`: 对应的候选转换结果，`convert` 始终取第一个候选
+    pub fn add_word(&mut self, key: impl Into<String>, values: Vec<Arc<str>>) {
+        self.user_dict.add_word(key, values);
+    }
+
+    /// 批量添加或覆盖运行时词条，等价于对每一项调用 [`OpenCC::add_word`]
+    pub fn add_words<I, K>(&mut self, words: I)
+    where
+        I: IntoIterator<Item = (K, Vec<Arc<str>>)>,
+        K: Into<String>,
+    {
+        for (key, values) in words {
+            self.add_word(key, values);
+        }
+    }
+
     /// 根据加载的配置转换字符串
     ///
     /// # 参数
@@ -95,7 +167,215 @@ impl OpenCC {
     ///
     /// 转换后的字符串
     pub fn convert(&self, input: &str) -> String {
-        self.conversion_chain.convert(input)
+        let normalized = crate::normalize::normalize(input);
+
+        let mut result = String::with_capacity(normalized.len());
+        let segments =
+            presegment::split(&normalized, &self.never_cross_delimiters, &self.extra_cjk_chars);
+        for segment in segments {
+            match segment {
+                presegment::Segment::Cjk(text) => {
+                    for word in self.cjk_words(text) {
+                        result.push_str(&self.conversion_chain.convert(word));
+                    }
+                }
+                presegment::Segment::Passthrough(text) => result.push_str(text),
+            }
+        }
+        result
+    }
+
+    /// 把一个 CJK 片段按配置的分词器切成若干个词；没有配置分词器时整个片段就是唯一
+    /// 一个词，转换链沿用它自己的贪婪匹配决定内部边界（与引入分词器之前的行为一致）。
+    ///
+    /// 分词得到的边界对转换链是硬边界：词典匹配不会跨越它，因此换一种分词算法
+    /// 可以改变存在歧义时的切分结果。
+    fn cjk_words<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        match &self.segmenter {
+            Some(segmenter) => segmenter.segment(text),
+            None => vec![text],
+        }
+    }
+
+    /// 返回每个词典命中位置的全部候选目标值，而不是像 [`OpenCC::convert`] 那样
+    /// 总是只保留第一个候选；未命中任何词典规则的片段原样保留。
+    ///
+    /// 转换链中除最后一步之外的所有步骤仍按默认策略（总是取第一个候选）运行，
+    /// 因为后续步骤需要看到完整的词组才能匹配；只有最后一步的候选会被完整保留下来。
+    /// 供下游构建交互式候选选择器或重排序器使用。
+    ///
+    /// # 参数
+    ///
+    /// * `input`: 需要转换的字符串
+    ///
+    /// # 返回
+    ///
+    /// 按原始顺序排列的转换片段
+    pub fn convert_candidates(&self, input: &str) -> Vec<conversion::Segment> {
+        let normalized = crate::normalize::normalize(input);
+
+        let mut segments = Vec::new();
+        let presegments =
+            presegment::split(&normalized, &self.never_cross_delimiters, &self.extra_cjk_chars);
+        for segment in presegments {
+            match segment {
+                presegment::Segment::Cjk(text) => {
+                    for word in self.cjk_words(text) {
+                        segments.extend(self.conversion_chain.convert_candidates(word));
+                    }
+                }
+                presegment::Segment::Passthrough(text) => {
+                    segments.push(conversion::Segment::Unmatched(text.to_string()));
+                }
+            }
+        }
+        segments
+    }
+
+    /// 与 [`OpenCC::convert`] 相同，但每个词典命中位置的候选改由 `selector` 选择，
+    /// 而不是总是取第一个候选。
+    ///
+    /// # 参数
+    ///
+    /// * `input`: 需要转换的字符串
+    /// * `selector`: 对每个命中位置调用一次，接收匹配到的源键与全部候选，返回要采用的候选下标；
+    ///   下标越界时回退到第一个候选
+    ///
+    /// # 返回
+    ///
+    /// 转换后的字符串
+    pub fn convert_with_selector(
+        &self,
+        input: &str,
+        mut selector: impl FnMut(&str, &[Arc<str>]) -> usize,
+    ) -> String {
+        let normalized = crate::normalize::normalize(input);
+
+        let mut result = String::with_capacity(normalized.len());
+        let segments =
+            presegment::split(&normalized, &self.never_cross_delimiters, &self.extra_cjk_chars);
+        for segment in segments {
+            match segment {
+                presegment::Segment::Cjk(text) => {
+                    for word in self.cjk_words(text) {
+                        result.push_str(
+                            &self
+                                .conversion_chain
+                                .convert_with_selector(word, &mut selector),
+                        );
+                    }
+                }
+                presegment::Segment::Passthrough(text) => result.push_str(text),
+            }
+        }
+        result
+    }
+
+    /// 以有界内存流式转换，适用于无法一次性放入内存的大文件。
+    ///
+    /// 从 `reader` 按块读取输入，转换后立即写入 `writer`，而不是像 [`OpenCC::convert`]
+    /// 那样要求整个文档同时以 `&str` 和 `String` 的形式存在于内存中。
+    ///
+    /// # 参数
+    ///
+    /// * `reader`: 提供 UTF-8 编码输入的来源
+    /// * `writer`: 接收转换结果的目的地
+    ///
+    /// # 返回
+    ///
+    /// 转换成功时返回 `Ok(())`；输入中出现非法 UTF-8 字节序列或底层 I/O 失败时返回错误
+    pub fn convert_stream<R: Read, W: Write>(&self, mut reader: R, mut writer: W) -> io::Result<()> {
+        const CHUNK_BYTES: usize = 64 * 1024;
+
+        // 最长词典键可能跨越块边界，因此每次只转换并输出到安全边界为止，
+        // 把末尾可能还需要更多前瞻字符的部分留到下一块再处理
+        let carry_chars = self.conversion_chain.max_key_length().saturating_sub(1);
+
+        let mut pending = String::new();
+        let mut raw = vec![0u8; CHUNK_BYTES];
+        let mut incomplete: Vec<u8> = Vec::new();
+
+        loop {
+            let n = reader.read(&mut raw)?;
+            if n == 0 {
+                if !incomplete.is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "输入在结尾处存在不完整的 UTF-8 字节序列",
+                    ));
+                }
+                if !pending.is_empty() {
+                    writer.write_all(self.convert(&pending).as_bytes())?;
+                }
+                break;
+            }
+
+            // 新读取的字节可能在多字节字符中间截断，先并入上次剩余的不完整字节，
+            // 只把已经构成合法 UTF-8 的前缀追加进 `pending`
+            incomplete.extend_from_slice(&raw[..n]);
+            let valid_len = match std::str::from_utf8(&incomplete) {
+                Ok(_) => incomplete.len(),
+                Err(e) => e.valid_up_to(),
+            };
+            pending.push_str(std::str::from_utf8(&incomplete[..valid_len]).expect("按合法 UTF-8 前缀切分"));
+            incomplete.drain(..valid_len);
+
+            let boundary = self.stream_flush_boundary(&pending, carry_chars);
+            if boundary > 0 {
+                writer.write_all(self.convert(&pending[..boundary]).as_bytes())?;
+                pending.drain(..boundary);
+            }
+        }
+
+        writer.flush()
+    }
+
+    /// 计算 `pending` 中可以安全转换并输出的字节边界。
+    ///
+    /// 按预分段切出末尾片段：透传片段不可能成为词典匹配的前缀，没有任何跨块
+    /// 前瞻需要，直接整体 flush（否则对一个没有任何分隔符的超大非 CJK 输入，
+    /// 例如整个压缩/base64 过的文件，`pending` 会一直攒到文件末尾才统一输出，
+    /// 内存占用随文件大小线性增长，违背流式转换“内存有界”的设计目的）；
+    /// 只有末尾是 CJK 片段时才需要保留它末尾 `carry_chars` 个字符，其余部分
+    /// 已经足够安全，可以立即转换输出。
+    fn stream_flush_boundary(&self, pending: &str, carry_chars: usize) -> usize {
+        let segments =
+            presegment::split(pending, &self.never_cross_delimiters, &self.extra_cjk_chars);
+        let Some(last) = segments.last() else {
+            return 0;
+        };
+
+        let presegment::Segment::Cjk(last_str) = *last else {
+            return self.normalization_safe_boundary(pending, pending.len());
+        };
+
+        // 链上所有词典的最长键都只有 1 个字符，不存在跨块匹配的风险，
+        // 和上面的透传分支一样可以直接整体 flush，否则纯 CJK 输入会一直
+        // 攒到文件末尾才统一输出，内存占用随文件大小线性增长
+        if carry_chars == 0 {
+            return self.normalization_safe_boundary(pending, pending.len());
+        }
+
+        let prefix_len = pending.len() - last_str.len();
+        let keep_from = last_str
+            .char_indices()
+            .rev()
+            .nth(carry_chars - 1)
+            .map(|(idx, _)| idx)
+            .unwrap_or(0);
+        self.normalization_safe_boundary(pending, prefix_len + keep_from)
+    }
+
+    /// 在词典安全边界 `boundary` 的基础上继续回退，确保切点不会落在一个
+    /// “基字符 + 组合标记”序列中间，也不会落在一个后续读取可能还会为其
+    /// 续上组合标记的基字符之后——否则跨块分别 normalize 两段文本，合成
+    /// 结果可能与整段一次性 normalize 不同，破坏“输出与整串转换一致”的
+    /// 保证。未启用任何规范化 feature 时不存在这个问题，原样返回 `boundary`。
+    fn normalization_safe_boundary(&self, pending: &str, boundary: usize) -> usize {
+        if crate::normalize::current_form() == crate::normalize::NormalizationForm::None {
+            return boundary;
+        }
+        backoff_combining_boundary(pending, boundary)
     }
 
     /// 返回当前加载的配置名称
@@ -103,3 +383,254 @@ impl OpenCC {
         &self.name
     }
 }
+
+/// 从 `boundary` 开始向前回退，跳过任何一段连续的组合标记，再多回退一个它们
+/// 依附的基字符，返回回退后的字节偏移。
+///
+/// 这个基字符随时可能在下一个读取块里被续上新的组合标记，必须和已经回退掉的
+/// 组合标记一起留到下一轮，否则跨块分别 normalize 两段文本，合成结果可能与
+/// 整段一次性 normalize 不同。只在启用了某种规范化形式时才需要调用，
+/// 因此和是否启用规范化 feature 的判断分离出来，方便单独测试。
+fn backoff_combining_boundary(text: &str, boundary: usize) -> usize {
+    let mut safe = boundary;
+    while let Some((idx, ch)) = text[..safe].char_indices().next_back() {
+        safe = idx;
+        if !normalize::is_combining_mark(ch) {
+            break;
+        }
+    }
+    safe
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::Cursor;
+
+    #[derive(Debug, Default)]
+    struct MockDict {
+        entries: HashMap<String, Vec<Arc<str>>>,
+        max_key_length: usize,
+    }
+
+    impl MockDict {
+        fn add_entry(&mut self, key: &str, value: &str) {
+            self.entries.insert(key.to_string(), vec![Arc::from(value)]);
+            self.max_key_length = self.max_key_length.max(key.len());
+        }
+    }
+
+    impl Dictionary for MockDict {
+        fn match_prefix<'b>(&self, word: &'b str) -> Option<(&'b str, Arc<[Arc<str>]>)> {
+            let mut longest_match_len = 0;
+            let mut result = None;
+            for (key, values) in &self.entries {
+                if word.starts_with(key.as_str()) && key.len() > longest_match_len {
+                    longest_match_len = key.len();
+                    result = Some((&word[..key.len()], values.as_slice().into()));
+                }
+            }
+            result
+        }
+
+        fn max_key_length(&self) -> usize {
+            self.max_key_length
+        }
+    }
+
+    /// 把一个 `&[u8]` 包装成每次最多只返回 `chunk_size` 字节的 `Read`，
+    /// 用来在测试里强制 `convert_stream` 频繁重新填充缓冲区，
+    /// 从而让多字节字符和词典键都有机会被截断在块边界上。
+    struct ChunkedReader<'a> {
+        data: &'a [u8],
+        pos: usize,
+        chunk_size: usize,
+    }
+
+    impl<'a> Read for ChunkedReader<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let remaining = &self.data[self.pos..];
+            let n = remaining.len().min(self.chunk_size).min(buf.len());
+            buf[..n].copy_from_slice(&remaining[..n]);
+            self.pos += n;
+            Ok(n)
+        }
+    }
+
+    fn test_opencc() -> OpenCC {
+        let mut dict = MockDict::default();
+        dict.add_entry("一个", "一個");
+        dict.add_entry("一个半", "一個半");
+        dict.add_entry("项目", "項目");
+        dict.add_entry("测试", "測試");
+
+        OpenCC {
+            name: "test".to_string(),
+            conversion_chain: ConversionChain::for_test(vec![Arc::new(dict)]),
+            user_dict: Arc::new(UserDict::new()),
+            never_cross_delimiters: Vec::new(),
+            extra_cjk_chars: Vec::new(),
+            segmenter: None,
+        }
+    }
+
+    #[test]
+    fn test_convert_stream_matches_convert_when_fed_one_byte_at_a_time() {
+        let opencc = test_opencc();
+        // 重复混合 ASCII 透传片段与会被词典命中的 CJK 片段，既覆盖跨多字节字符的
+        // 切分，也覆盖跨词典键（如“一个半” vs “一个”）的切分
+        let input = "Hello 一个项目 World 一个半小时 测试 end".repeat(20);
+
+        let expected = opencc.convert(&input);
+
+        let reader = ChunkedReader {
+            data: input.as_bytes(),
+            pos: 0,
+            chunk_size: 1,
+        };
+        let mut output = Vec::new();
+        opencc.convert_stream(reader, &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_convert_stream_matches_convert_across_varied_chunk_sizes() {
+        let opencc = test_opencc();
+        let input = "Hello 一个项目 World 一个半小时 测试 end".repeat(20);
+        let expected = opencc.convert(&input);
+
+        for chunk_size in [1, 2, 3, 5, 8, 64] {
+            let reader = ChunkedReader {
+                data: input.as_bytes(),
+                pos: 0,
+                chunk_size,
+            };
+            let mut output = Vec::new();
+            opencc.convert_stream(reader, &mut output).unwrap();
+            assert_eq!(
+                String::from_utf8(output).unwrap(),
+                expected,
+                "mismatch at chunk_size = {chunk_size}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_convert_stream_rejects_truncated_trailing_utf8() {
+        let opencc = test_opencc();
+        // "测" 的 UTF-8 编码是 3 字节，这里只截取前 2 字节，模拟输入在多字节字符
+        // 中间被截断结束的情况
+        let mut truncated = "一个".as_bytes().to_vec();
+        truncated.extend_from_slice(&"测".as_bytes()[..2]);
+
+        let reader = Cursor::new(truncated);
+        let mut output = Vec::new();
+        let err = opencc.convert_stream(reader, &mut output).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_stream_flush_boundary_flushes_entire_trailing_cjk_run_when_carry_is_zero() {
+        let opencc = test_opencc();
+        // 链上所有词典的最长键都只有 1 个字符时（`carry_chars == 0`），不存在跨块
+        // 匹配的风险，即使整个 `pending` 都是一段连续的 CJK 文本（没有任何非 CJK
+        // 分隔符），也必须整体 flush，否则内存会随输入长度无限增长
+        let pending = "一个项目一个项目一个项目";
+        assert_eq!(opencc.stream_flush_boundary(pending, 0), pending.len());
+    }
+
+    #[test]
+    fn test_backoff_combining_boundary_excludes_trailing_base_char_and_its_marks() {
+        // "cafe" + 组合重音符 U+0301，合起来表达 NFD 形式的 "café"
+        let text = "cafe\u{0301}";
+
+        // 天真地把边界设在基字符 'e' 和它的组合标记之间时，必须回退到 "caf" 之后，
+        // 把 'e' 和紧随其后的组合标记一起留到下一轮
+        let naive_boundary = "caf".len() + "e".len();
+        assert_eq!(backoff_combining_boundary(text, naive_boundary), "caf".len());
+
+        // 边界已经包含了组合标记本身时，同样要回退掉标记和它所依附的基字符
+        assert_eq!(backoff_combining_boundary(text, text.len()), "caf".len());
+
+        // 即使边界处的字符后面当前并没有跟着组合标记，也要回退一个基字符，
+        // 为它预留被下一个读取块续上组合标记的可能
+        assert_eq!(backoff_combining_boundary("caf", "caf".len()), "ca".len());
+
+        assert_eq!(backoff_combining_boundary("", 0), 0);
+    }
+
+    #[test]
+    fn test_hmm_segmenter_is_wired_and_invoked_for_out_of_vocabulary_runs() {
+        // 空词典：所有字符在分词阶段都是未登录词，整段都要交给 Viterbi 重新切分
+        let seg_dict: Arc<dyn Dictionary> = Arc::new(MockDict::default());
+        let segmenter: Box<dyn Segmentation> = Box::new(segmentation::HmmSegmentation::new(
+            seg_dict,
+            segmentation::hmm::HmmModel::embedded(),
+        ));
+
+        let opencc = OpenCC {
+            name: "test".to_string(),
+            conversion_chain: ConversionChain::for_test(vec![Arc::new(MockDict::default())]),
+            user_dict: Arc::new(UserDict::new()),
+            never_cross_delimiters: Vec::new(),
+            extra_cjk_chars: Vec::new(),
+            segmenter: Some(segmenter),
+        };
+
+        // 没有分词器时，一个 CJK 片段永远整段当一个词交给转换链（见 `cjk_words`）。
+        // 配置了 HMM 分词器后，它必须真正参与切分——具体切法由 Viterbi 的种子
+        // 概率决定，这里不假设切分结果，只验证它确实不再是“整段一个词”，
+        // 且没有丢字或产生越界片段。
+        let words = opencc.cjk_words("的未登录词的测试");
+        assert_ne!(words, vec!["的未登录词的测试"]);
+        assert_eq!(words.iter().map(|w| w.chars().count()).sum::<usize>(), 8);
+    }
+
+    #[test]
+    fn test_bimm_segmenter_forces_hard_word_boundaries_on_ambiguous_cjk_runs() {
+        // 经典的正向/反向最大匹配歧义样例："研究生命起源"。转换链自己的贪婪匹配
+        // 等价于正向最大匹配，会先吞下最长的"研究生"；配置 bimm 分词器后，歧义
+        // 在转换链看到文本之前就已按"研究/生命/起源"解出，词边界成为转换链不能
+        // 跨越的硬边界，因此"生命"会整体命中，而不是被"研究生"切掉一个字剩下
+        // 孤立的"命"。
+        let build_conversion_chain = || {
+            let mut dict = MockDict::default();
+            dict.add_entry("研究生", "甲");
+            dict.add_entry("研究", "乙");
+            dict.add_entry("命", "丙");
+            dict.add_entry("生命", "丁");
+            dict.add_entry("起源", "戊");
+            ConversionChain::for_test(vec![Arc::new(dict)])
+        };
+
+        let without_segmenter = OpenCC {
+            name: "test".to_string(),
+            conversion_chain: build_conversion_chain(),
+            user_dict: Arc::new(UserDict::new()),
+            never_cross_delimiters: Vec::new(),
+            extra_cjk_chars: Vec::new(),
+            segmenter: None,
+        };
+        assert_eq!(without_segmenter.convert("研究生命起源"), "甲丙戊");
+
+        let mut seg_dict = MockDict::default();
+        seg_dict.add_entry("研究", "_");
+        seg_dict.add_entry("研究生", "_");
+        seg_dict.add_entry("生命", "_");
+        seg_dict.add_entry("起源", "_");
+        let segmenter: Box<dyn Segmentation> =
+            Box::new(segmentation::BiMaxMatchSegmentation::new(Arc::new(seg_dict)));
+
+        let with_segmenter = OpenCC {
+            name: "test".to_string(),
+            conversion_chain: build_conversion_chain(),
+            user_dict: Arc::new(UserDict::new()),
+            never_cross_delimiters: Vec::new(),
+            extra_cjk_chars: Vec::new(),
+            segmenter: Some(segmenter),
+        };
+        assert_eq!(with_segmenter.convert("研究生命起源"), "乙丁戊");
+    }
+}
@@ -0,0 +1,73 @@
+//! 输入文本的 Unicode 规范化处理
+//!
+//! 通过互斥的 feature（`nfc`/`nfd`/`nfkc`/`nfkd`）选择规范化形式，
+//! 默认不启用任何 feature 时为直通（不做任何处理）。
+//! 词典编译时（`compiler_logic.rs`）会使用同样的形式规范化键，
+//! 使查询路径与编译出的 FST 保持一致。
+
+use bincode::{Decode, Encode};
+use std::borrow::Cow;
+
+#[cfg(any(
+    all(feature = "nfc", feature = "nfd"),
+    all(feature = "nfc", feature = "nfkc"),
+    all(feature = "nfc", feature = "nfkd"),
+    all(feature = "nfd", feature = "nfkc"),
+    all(feature = "nfd", feature = "nfkd"),
+    all(feature = "nfkc", feature = "nfkd"),
+))]
+compile_error!("features `nfc`、`nfd`、`nfkc` 和 `nfkd` 互斥，最多只能启用一个");
+
+/// 词典编译/查询时使用的 Unicode 规范化形式
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizationForm {
+    /// 不做任何规范化
+    None,
+    /// Normalization Form C（规范组合）
+    Nfc,
+    /// Normalization Form D（规范分解）
+    Nfd,
+    /// Normalization Form KC（兼容组合）
+    Nfkc,
+    /// Normalization Form KD（兼容分解）
+    Nfkd,
+}
+
+/// 返回当前编译启用的规范化形式
+pub fn current_form() -> NormalizationForm {
+    #[cfg(feature = "nfc")]
+    return NormalizationForm::Nfc;
+    #[cfg(feature = "nfd")]
+    return NormalizationForm::Nfd;
+    #[cfg(feature = "nfkc")]
+    return NormalizationForm::Nfkc;
+    #[cfg(feature = "nfkd")]
+    return NormalizationForm::Nfkd;
+
+    #[cfg(not(any(feature = "nfc", feature = "nfd", feature = "nfkc", feature = "nfkd")))]
+    return NormalizationForm::None;
+}
+
+/// 按当前启用的形式规范化输入字符串。
+/// 未启用任何规范化 feature 时原样借用，不产生分配。
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    use unicode_normalization::UnicodeNormalization;
+
+    match current_form() {
+        NormalizationForm::None => Cow::Borrowed(input),
+        NormalizationForm::Nfc => Cow::Owned(input.nfc().collect()),
+        NormalizationForm::Nfd => Cow::Owned(input.nfd().collect()),
+        NormalizationForm::Nfkc => Cow::Owned(input.nfkc().collect()),
+        NormalizationForm::Nfkd => Cow::Owned(input.nfkd().collect()),
+    }
+}
+
+/// 判断一个字符是否是依附在前一个字符上的组合标记（如重音符号）。
+///
+/// 流式转换据此避免在“基字符 + 组合标记”序列中间切块：分两段分别规范化
+/// 会产生和整段一次性规范化不同的结果，因此组合标记永远不能单独成为
+/// 一块的开头。未启用任何规范化 feature 时不存在这个问题（[`normalize`]
+/// 原样直通），调用方应先检查 [`current_form`]。
+pub fn is_combining_mark(ch: char) -> bool {
+    unicode_normalization::char::is_combining_mark(ch)
+}
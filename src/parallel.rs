@@ -0,0 +1,43 @@
+//! 基于 `rayon` 的并行批量/大文档转换
+//!
+//! `OpenCC` 的所有词典都以 `Arc<dyn Dictionary>` 持有，转换流程也只需要 `&self`，
+//! 转换状态天然可以在线程间共享，无需调用方手动分片或克隆实例。
+
+use crate::OpenCC;
+use rayon::prelude::*;
+
+impl OpenCC {
+    /// 并行转换一批相互独立的文本，返回值与 `inputs` 一一对应。
+    ///
+    /// # 参数
+    ///
+    /// * `inputs`: 一批待转换的文本
+    ///
+    /// # 返回
+    ///
+    /// 与 `inputs` 顺序一致的转换结果
+    pub fn convert_batch(&self, inputs: &[&str]) -> Vec<String> {
+        inputs.par_iter().map(|input| self.convert(input)).collect()
+    }
+
+    /// 把单个大文档按换行符切分成片段并行转换，再按原始顺序拼接回去。
+    ///
+    /// 换行符是词典键不会跨越的安全切分点，因此各片段可以独立转换而不影响结果。
+    ///
+    /// # 参数
+    ///
+    /// * `text`: 需要转换的大文档
+    ///
+    /// # 返回
+    ///
+    /// 转换后的字符串，与 [`OpenCC::convert`] 处理整个文档得到的结果一致
+    pub fn convert_parallel(&self, text: &str) -> String {
+        // `split_inclusive` 保留换行符，拼接时不需要再把它们补回去
+        let segments: Vec<&str> = text.split_inclusive('\n').collect();
+        segments
+            .into_par_iter()
+            .map(|segment| self.convert(segment))
+            .collect::<Vec<String>>()
+            .concat()
+    }
+}
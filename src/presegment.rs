@@ -0,0 +1,192 @@
+//! 按 CJK / 非 CJK 边界对输入做预分段
+//!
+//! `OpenCC::convert` 此前会把整个输入(包括 ASCII、空白、数字、标点、代码)
+//! 逐字符地喂给词典查找，这些位置永远不可能命中，却仍要白白走一遍
+//! `match_prefix`，而且理论上还可能让一个词典键跨越句子边界去匹配。
+//! 这里先把输入切成连续的 CJK 片段与非 CJK 片段，只把 CJK 片段交给
+//! 转换链处理，非 CJK 片段原样透传。
+
+/// 一次预分段得到的片段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// 需要交给转换链处理的 CJK 片段
+    Cjk(&'a str),
+    /// 原样透传、不参与词典匹配的非 CJK 片段
+    Passthrough(&'a str),
+}
+
+/// 判断一个字符是否属于本库认为需要参与转换的 CJK 范围
+///
+/// 除表意文字本身外，也包含词典规则常用到的 CJK 标点、引号和全角符号
+/// （如直角引号「」『』、弯引号“”‘’、句号。逗号，顿号、等），
+/// 否则像简繁之间的引号风格转换这类规则会因为标点被当成透传字符而永远无法命中。
+pub fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x3000..=0x303F     // CJK 符号和标点（。，、「」『』等）
+        | 0x3400..=0x4DBF   // CJK 扩展 A
+        | 0x4E00..=0x9FFF   // CJK 统一表意文字
+        | 0xF900..=0xFAFF   // CJK 兼容表意文字
+        | 0xFF00..=0xFFEF   // 全角字符和半角字符
+        | 0x20000..=0x2A6DF // CJK 扩展 B
+        | 0x2A700..=0x2EBEF // CJK 扩展 C~F
+    ) || matches!(ch, '\u{2018}'..='\u{201F}') // 常用弯引号“”‘’及相关标点
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Kind {
+    Cjk,
+    Passthrough,
+}
+
+fn push<'a>(segments: &mut Vec<Segment<'a>>, text: &'a str, kind: Kind, range: std::ops::Range<usize>) {
+    if range.is_empty() {
+        return;
+    }
+    let slice = &text[range];
+    segments.push(match kind {
+        Kind::Cjk => Segment::Cjk(slice),
+        Kind::Passthrough => Segment::Passthrough(slice),
+    });
+}
+
+/// 把输入切成连续的 CJK 片段与非 CJK 片段。
+///
+/// `never_cross` 中列出的字符永远单独成为自己的一个透传片段——即使它本身落在
+/// CJK 范围内——用于强制在特定分隔符（如用户指定的句读符号）处切断，
+/// 防止词典键跨越这些边界进行匹配。
+///
+/// `extra_cjk` 中列出的字符即使不落在 [`is_cjk`] 内置的范围表里，也会被当成
+/// CJK 字符参与分段，用于覆盖内置范围表之外、某个配置特有的边界字符类；
+/// 两者同时列出同一个字符时 `never_cross` 优先。
+pub fn split<'a>(text: &'a str, never_cross: &[char], extra_cjk: &[char]) -> Vec<Segment<'a>> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut current: Option<Kind> = None;
+
+    for (idx, ch) in text.char_indices() {
+        if never_cross.contains(&ch) {
+            if let Some(kind) = current.take() {
+                push(&mut segments, text, kind, start..idx);
+            }
+            let ch_end = idx + ch.len_utf8();
+            push(&mut segments, text, Kind::Passthrough, idx..ch_end);
+            start = ch_end;
+            continue;
+        }
+
+        let kind = if is_cjk(ch) || extra_cjk.contains(&ch) {
+            Kind::Cjk
+        } else {
+            Kind::Passthrough
+        };
+        match current {
+            Some(k) if k == kind => {}
+            Some(k) => {
+                push(&mut segments, text, k, start..idx);
+                start = idx;
+                current = Some(kind);
+            }
+            None => {
+                start = idx;
+                current = Some(kind);
+            }
+        }
+    }
+
+    if let Some(kind) = current {
+        push(&mut segments, text, kind, start..text.len());
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_separates_cjk_and_passthrough_runs() {
+        let segments = split("Hello世界! 123", &[], &[]);
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Passthrough("Hello"),
+                Segment::Cjk("世界"),
+                Segment::Passthrough("! 123"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_cjk_includes_punctuation_and_fullwidth_forms() {
+        // CJK 符号和标点
+        assert!(is_cjk('。'));
+        assert!(is_cjk('，'));
+        assert!(is_cjk('、'));
+        assert!(is_cjk('「'));
+        assert!(is_cjk('」'));
+        assert!(is_cjk('『'));
+        assert!(is_cjk('』'));
+        // 常用弯引号
+        assert!(is_cjk('“'));
+        assert!(is_cjk('”'));
+        assert!(is_cjk('‘'));
+        assert!(is_cjk('’'));
+        // 全角字符
+        assert!(is_cjk('Ａ'));
+        assert!(is_cjk('！'));
+
+        assert!(!is_cjk('a'));
+        assert!(!is_cjk('!'));
+    }
+
+    #[test]
+    fn test_split_keeps_quotes_in_the_same_cjk_segment_as_surrounding_text() {
+        // 引号必须和两侧的表意文字连成同一个 CJK 片段，否则词典里的引号风格转换规则
+        // （如“”-> 「」）永远不会有机会命中，参见 crate 文档示例
+        let segments = split("“开放中文转换”是完全由 Rust 实现的。", &[], &[]);
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Cjk("“开放中文转换”是完全由"),
+                Segment::Passthrough(" Rust "),
+                Segment::Cjk("实现的。"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_honors_never_cross_delimiters() {
+        // “的”本身落在 CJK 范围内，正常情况下会和相邻汉字连成一个片段；
+        // 把它放进 never_cross 后，即使两侧都是 CJK 字符，也必须在它处断开
+        let segments = split("你好的世界", &['的'], &[]);
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Cjk("你好"),
+                Segment::Passthrough("的"),
+                Segment::Cjk("世界"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_honors_extra_cjk_chars() {
+        // '~' 不在内置的 CJK 范围表里，正常情况下会和相邻汉字断成两个片段；
+        // 把它加入 extra_cjk 后，必须和两侧的表意文字合并成同一个 CJK 片段
+        let segments = split("你好~世界", &[], &['~']);
+        assert_eq!(segments, vec![Segment::Cjk("你好~世界")]);
+
+        // 同一个字符同时出现在 never_cross 里时，never_cross 优先，仍然强制断开
+        let segments = split("你好~世界", &['~'], &['~']);
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Cjk("你好"),
+                Segment::Passthrough("~"),
+                Segment::Cjk("世界"),
+            ]
+        );
+    }
+}
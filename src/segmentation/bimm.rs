@@ -0,0 +1,85 @@
+//! 双向最大匹配分词
+//!
+//! 单纯的正向最大匹配在有歧义的重叠片段上会产生经典的"贪心误切"问题。
+//! 这里同时跑一遍正向最大匹配（FMM）和反向最大匹配（RMM），再按经典的
+//! 启发式规则二选一：词数更少的胜出；词数相同时单字词更少的胜出；
+//! 再相同则偏向反向结果（经验上对中文更准确）。
+
+use crate::dictionary::Dictionary;
+
+/// 反向（后缀优先）最大匹配。
+/// 由于 `Dictionary` 只提供 `match_prefix`，这里从候选窗口长度
+/// 从 `max_key_length` 到 1 依次在词尾探测，找到能被词典完整匹配的最长窗口。
+pub fn segment_reverse<'a>(dict: &dyn Dictionary, text: &'a str) -> Vec<&'a str> {
+    // 预先收集所有字符边界（含末尾），便于从右向左按字符数回退
+    let boundaries: Vec<usize> = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(text.len()))
+        .collect();
+
+    if boundaries.len() <= 1 {
+        return Vec::new();
+    }
+
+    let max_window = dict.max_key_length().max(1);
+    let mut segments = Vec::new();
+    let mut end_idx = boundaries.len() - 1;
+
+    while end_idx > 0 {
+        let end = boundaries[end_idx];
+        let longest_window = max_window.min(end_idx);
+        let mut matched = false;
+
+        for window_chars in (1..=longest_window).rev() {
+            let start_idx = end_idx - window_chars;
+            let start = boundaries[start_idx];
+            let window = &text[start..end];
+
+            if let Some((key, _)) = dict.match_prefix(window) {
+                if key.len() == window.len() {
+                    segments.push(window);
+                    end_idx = start_idx;
+                    matched = true;
+                    break;
+                }
+            }
+        }
+
+        if !matched {
+            // 没有任何窗口能被词典完整匹配，回退为词尾的单个字符
+            let start_idx = end_idx - 1;
+            segments.push(&text[boundaries[start_idx]..end]);
+            end_idx = start_idx;
+        }
+    }
+
+    segments.reverse();
+    segments
+}
+
+/// 按"词数更少 > 单字词更少 > 偏向反向"的顺序，从正向/反向结果中选出更优的切分
+pub fn pick_better<'a>(forward: Vec<&'a str>, reverse: Vec<&'a str>) -> Vec<&'a str> {
+    if forward.len() != reverse.len() {
+        return if forward.len() < reverse.len() {
+            forward
+        } else {
+            reverse
+        };
+    }
+
+    let count_singles = |segs: &[&str]| segs.iter().filter(|s| s.chars().count() == 1).count();
+    let forward_singles = count_singles(&forward);
+    let reverse_singles = count_singles(&reverse);
+
+    if forward_singles != reverse_singles {
+        if forward_singles < reverse_singles {
+            forward
+        } else {
+            reverse
+        }
+    } else {
+        // 完全打平时，经验上反向最大匹配更准确
+        reverse
+    }
+}
@@ -0,0 +1,235 @@
+//! 基于字符级 BMES 隐马尔可夫模型的分词回退
+//!
+//! 正向最大匹配在遇到词典之外的字符时会逐字回退，这会把未登录词
+//! （人名、新词等）拆成孤立的单字。这里用经典的 BMES
+//! （Begin/Middle/End/Single）四状态模型对连续的单字回退片段做
+//! Viterbi 重新切分，尝试恢复完整的词。
+
+use bincode::{Decode, Encode, config};
+use fst::Map;
+
+/// BMES 标注状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    B = 0,
+    M = 1,
+    E = 2,
+    S = 3,
+}
+
+const STATES: [State; 4] = [State::B, State::M, State::E, State::S];
+const NUM_STATES: usize = 4;
+const NEG_INF: f64 = f64::NEG_INFINITY;
+
+/// 内置的 BMES 训练语料，编译时直接嵌入二进制；`HmmModel::seed` 通过
+/// `compiler::compile_hmm_emissions` 把它编译成真正按字符统计出的发射概率表
+const TRAINING_CORPUS: &str = include_str!("hmm_corpus.txt");
+
+/// 与 `compiler::compile_hmm_emissions` 打包格式一一对应的解码结构体
+///
+/// 两边各自维护一份定义（而不是共享一个类型），和 `SerializableFstDict` 在
+/// `compiler_logic.rs`/`fst_dict.rs` 中的做法一致：bincode 按字段顺序编解码，
+/// 不要求编译端和加载端处于同一个模块。
+#[derive(Encode, Decode)]
+struct SerializableHmmEmissions {
+    /// zstd 压缩后的 `[Vec<f64>; 4]`，按 B/M/E/S 顺序存放各状态下字符发射对数概率表
+    compressed_probs: Vec<u8>,
+    /// 每个状态下字符到 `compressed_probs` 解压后对应表中下标的 FST 原始字节
+    fst_bytes: [Vec<u8>; NUM_STATES],
+    /// 字符未出现在对应状态的发射表中时使用的下限对数概率
+    emission_floor: [f64; NUM_STATES],
+}
+
+/// 字符级 HMM 分词模型，持有 BMES 的初始、转移与发射对数概率
+///
+/// 内置的发射表由 [`TRAINING_CORPUS`] 中的常见汉语词编译而来：按字符在
+/// 词中的位置统计频次，加一平滑后取对数得到概率，再为每个状态建一个
+/// 字符到概率表下标的 FST，查找方式与词典的前缀匹配同源。
+pub struct HmmModel {
+    /// 初始状态对数概率，顺序为 [B, M, E, S]；一个词只能以 B 或 S 开始，M、E 为 -inf
+    initial: [f64; NUM_STATES],
+    /// 转移对数概率矩阵 `transition[from][to]`；非法转移为 -inf
+    transition: [[f64; NUM_STATES]; NUM_STATES],
+    /// 每个状态下，字符到 `emission_probs` 对应表中下标的 FST
+    emission_index: [Map<Vec<u8>>; NUM_STATES],
+    /// 每个状态下字符的发射对数概率，下标由同状态的 `emission_index` 给出
+    emission_probs: [Vec<f64>; NUM_STATES],
+    /// 字符未出现在发射表中时使用的下限对数概率，按状态区分
+    emission_floor: [f64; NUM_STATES],
+}
+
+impl std::fmt::Debug for HmmModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // `fst::Map` 不实现 `Debug`，而且打印整张发射表也没有调试价值，
+        // 这里和 `FstDict`/`DictGroup` 一样只打印有用的元信息
+        f.debug_struct("HmmModel")
+            .field(
+                "emission_vocab",
+                &self.emission_probs.iter().map(Vec::len).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl HmmModel {
+    /// 返回进程内共享的内置模型
+    pub fn embedded() -> &'static HmmModel {
+        static MODEL: std::sync::OnceLock<HmmModel> = std::sync::OnceLock::new();
+        MODEL.get_or_init(HmmModel::seed)
+    }
+
+    /// 构建内置的种子概率表：初始/转移概率沿用手工设定的合理值，
+    /// 发射概率表则从 [`TRAINING_CORPUS`] 编译而来，真正按字符统计得到
+    fn seed() -> Self {
+        // 初始概率：大多数词以 B 开始，少数是单字词
+        let initial = [(0.6f64).ln(), NEG_INF, NEG_INF, (0.4f64).ln()];
+
+        // 合法转移：B->M/E，M->M/E，E->B/S，S->B/S，其余为 -inf
+        let mut transition = [[NEG_INF; NUM_STATES]; NUM_STATES];
+        transition[State::B as usize][State::M as usize] = (0.3f64).ln();
+        transition[State::B as usize][State::E as usize] = (0.7f64).ln();
+        transition[State::M as usize][State::M as usize] = (0.3f64).ln();
+        transition[State::M as usize][State::E as usize] = (0.7f64).ln();
+        transition[State::E as usize][State::B as usize] = (0.5f64).ln();
+        transition[State::E as usize][State::S as usize] = (0.5f64).ln();
+        transition[State::S as usize][State::B as usize] = (0.5f64).ln();
+        transition[State::S as usize][State::S as usize] = (0.5f64).ln();
+
+        let compiled = crate::compiler::compile_hmm_emissions(TRAINING_CORPUS)
+            .expect("内置 HMM 训练语料格式错误");
+        let (emission_index, emission_probs, emission_floor) =
+            Self::decode_emissions(&compiled).expect("内置 HMM 发射概率表解码失败");
+
+        Self {
+            initial,
+            transition,
+            emission_index,
+            emission_probs,
+            emission_floor,
+        }
+    }
+
+    /// 解码 `compiler::compile_hmm_emissions` 产出的字节，还原发射表的三个部分
+    fn decode_emissions(
+        bytes: &[u8],
+    ) -> crate::error::Result<([Map<Vec<u8>>; NUM_STATES], [Vec<f64>; NUM_STATES], [f64; NUM_STATES])> {
+        let (metadata, _): (SerializableHmmEmissions, usize) =
+            bincode::decode_from_slice(bytes, config::standard())?;
+
+        let probs_bytes = zstd::decode_all(&metadata.compressed_probs[..])?;
+        let (probs, _): ([Vec<f64>; NUM_STATES], usize) =
+            bincode::decode_from_slice(&probs_bytes, config::standard())?;
+
+        let mut fst_bytes = metadata.fst_bytes.into_iter();
+        let mut emission_index: [Option<Map<Vec<u8>>>; NUM_STATES] = std::array::from_fn(|_| None);
+        for slot in &mut emission_index {
+            let bytes = fst_bytes.next().expect("fst_bytes 长度固定为 NUM_STATES");
+            *slot = Some(Map::new(bytes)?);
+        }
+        let emission_index = emission_index.map(|m| m.expect("已在上面逐一填充"));
+
+        Ok((emission_index, probs, metadata.emission_floor))
+    }
+
+    /// 某个状态下出现某个字符的发射对数概率
+    fn emit(&self, state: State, ch: char) -> f64 {
+        let idx = state as usize;
+        let mut buf = [0u8; 4];
+        let key = ch.encode_utf8(&mut buf);
+        match self.emission_index[idx].get(key.as_bytes()) {
+            Some(value_index) => self.emission_probs[idx][value_index as usize],
+            None => self.emission_floor[idx],
+        }
+    }
+}
+
+/// 对一段字符运行 Viterbi 解码，返回每个字符对应的 BMES 状态
+fn viterbi(model: &HmmModel, chars: &[char]) -> Vec<State> {
+    if chars.is_empty() {
+        return Vec::new();
+    }
+
+    // `scores[t][s]` 是到时刻 t 为止，以状态 s 结尾的最优路径对数概率
+    let mut scores = vec![[NEG_INF; NUM_STATES]; chars.len()];
+    let mut backptr = vec![[0usize; NUM_STATES]; chars.len()];
+
+    for (s_idx, &state) in STATES.iter().enumerate() {
+        scores[0][s_idx] = model.initial[s_idx] + model.emit(state, chars[0]);
+    }
+
+    for t in 1..chars.len() {
+        for (s_idx, &state) in STATES.iter().enumerate() {
+            let emit = model.emit(state, chars[t]);
+            let mut best_score = NEG_INF;
+            let mut best_prev = 0;
+            for (p_idx, _) in STATES.iter().enumerate() {
+                let trans = model.transition[p_idx][s_idx];
+                if trans == NEG_INF || scores[t - 1][p_idx] == NEG_INF {
+                    continue;
+                }
+                let candidate = scores[t - 1][p_idx] + trans;
+                if candidate > best_score {
+                    best_score = candidate;
+                    best_prev = p_idx;
+                }
+            }
+            scores[t][s_idx] = best_score + emit;
+            backptr[t][s_idx] = best_prev;
+        }
+    }
+
+    // 一个完整的切分只能以 E 或 S 收尾，B/M 结尾意味着最后一个词没有结束
+    let last = chars.len() - 1;
+    let (e_idx, s_idx) = (State::E as usize, State::S as usize);
+    let best_last = if scores[last][e_idx] > scores[last][s_idx] {
+        e_idx
+    } else {
+        s_idx
+    };
+
+    let mut path = vec![0usize; chars.len()];
+    path[last] = best_last;
+    for t in (1..chars.len()).rev() {
+        path[t - 1] = backptr[t][path[t]];
+    }
+
+    path.into_iter().map(|idx| STATES[idx]).collect()
+}
+
+/// 用 Viterbi 对一段字符重新切分，在每个 B…E 区间以及每个 S 处切词
+pub fn resegment(model: &HmmModel, run: &str) -> Vec<&str> {
+    let char_indices: Vec<(usize, char)> = run.char_indices().collect();
+    let chars: Vec<char> = char_indices.iter().map(|(_, c)| *c).collect();
+    let states = viterbi(model, &chars);
+
+    let mut words = Vec::new();
+    let mut word_start: Option<usize> = None;
+
+    for (i, state) in states.iter().enumerate() {
+        let byte_start = char_indices[i].0;
+        match state {
+            State::B => word_start = Some(byte_start),
+            State::S => {
+                let ch_end = byte_start + chars[i].len_utf8();
+                words.push(&run[byte_start..ch_end]);
+                word_start = None;
+            }
+            State::E => {
+                let start = word_start.unwrap_or(byte_start);
+                let end = byte_start + chars[i].len_utf8();
+                words.push(&run[start..end]);
+                word_start = None;
+            }
+            State::M => {
+                // 仍在词中间，等待 E 结束
+            }
+        }
+    }
+
+    // 解码结果理论上总是以 E 或 S 收尾，但为了稳妥起见兜底剩余部分
+    if let Some(start) = word_start {
+        words.push(&run[start..]);
+    }
+
+    words
+}
@@ -1,8 +1,12 @@
 //! 提供文本分词功能
 
+pub mod bimm;
+pub mod hmm;
+
 use crate::config::SegmentationConfig;
 use crate::dictionary::{DictType, Dictionary};
 use crate::error::{OpenCCError, Result};
+use hmm::HmmModel;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -16,6 +20,10 @@ pub trait Segmentation: Send + Sync {
 pub enum SegmentationType {
     /// 代表最大匹配分词算法
     MaxMatch(Arc<dyn Dictionary>),
+    /// 代表在最大匹配的单字回退片段上叠加 HMM/Viterbi 重新切分
+    Hmm(Arc<dyn Dictionary>),
+    /// 代表双向最大匹配分词
+    BiMaxMatch(Arc<dyn Dictionary>),
 }
 
 impl SegmentationType {
@@ -26,6 +34,14 @@ impl SegmentationType {
                 let dict = DictType::from_config(&config.dict, config_dir)?;
                 Ok(SegmentationType::MaxMatch(dict))
             }
+            "hmm" => {
+                let dict = DictType::from_config(&config.dict, config_dir)?;
+                Ok(SegmentationType::Hmm(dict))
+            }
+            "bimm" => {
+                let dict = DictType::from_config(&config.dict, config_dir)?;
+                Ok(SegmentationType::BiMaxMatch(dict))
+            }
             _ => Err(OpenCCError::InvalidConfig(format!(
                 "Unsupported segmentation type: {}",
                 config.seg_type
@@ -34,13 +50,20 @@ impl SegmentationType {
     }
 
     /// 从嵌入式资源加载配置来创建分词器类型
-    #[cfg(feature = "embed-dictionaries")]
     pub fn from_config_embedded(config: &SegmentationConfig) -> Result<Self> {
         match config.seg_type.as_str() {
             "mm" | "mmseg" => {
                 let dict = DictType::from_config_embedded(&config.dict)?;
                 Ok(SegmentationType::MaxMatch(dict))
             }
+            "hmm" => {
+                let dict = DictType::from_config_embedded(&config.dict)?;
+                Ok(SegmentationType::Hmm(dict))
+            }
+            "bimm" => {
+                let dict = DictType::from_config_embedded(&config.dict)?;
+                Ok(SegmentationType::BiMaxMatch(dict))
+            }
             _ => Err(OpenCCError::InvalidConfig(format!(
                 "Unsupported segmentation type: {}",
                 config.seg_type
@@ -52,10 +75,38 @@ impl SegmentationType {
     pub fn into_segmenter(self) -> Box<dyn Segmentation> {
         match self {
             SegmentationType::MaxMatch(dict) => Box::new(MaxMatchSegmentation::new(dict)),
+            SegmentationType::Hmm(dict) => {
+                Box::new(HmmSegmentation::new(dict, HmmModel::embedded()))
+            }
+            SegmentationType::BiMaxMatch(dict) => Box::new(BiMaxMatchSegmentation::new(dict)),
         }
     }
 }
 
+/// 执行一次正向最大匹配，并为每个片段标注它是词典命中（`false`）
+/// 还是无匹配时的单字回退（`true`）
+fn max_match_tagged<'a>(dict: &dyn Dictionary, text: &'a str) -> Vec<(&'a str, bool)> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+
+    while start < text.len() {
+        let remaining_text = &text[start..];
+        if let Some((matched_key, _)) = dict.match_prefix(remaining_text) {
+            segments.push((matched_key, false));
+            start += matched_key.len();
+        } else {
+            let ch_end = remaining_text
+                .char_indices()
+                .nth(1)
+                .map_or(remaining_text.len(), |(idx, _)| idx);
+            segments.push((&remaining_text[..ch_end], true));
+            start += ch_end;
+        }
+    }
+
+    segments
+}
+
 /// 一个使用正向最大匹配算法的分词器。
 /// 该算法会贪婪地从词典中查找与剩余文本开头匹配的最长的词。
 pub struct MaxMatchSegmentation {
@@ -74,29 +125,79 @@ impl Segmentation for MaxMatchSegmentation {
     /// 遍历文本，在每个位置找到词典中能作为剩余文本前缀的最长词语。
     /// 如果没有找到匹配，则将当前位置的单个字符作为一个片段。
     fn segment<'a>(&self, text: &'a str) -> Vec<&'a str> {
-        let mut segments = Vec::new();
-        let mut start = 0;
-
-        while start < text.len() {
-            let remaining_text = &text[start..];
-            if let Some((matched_key, _)) = self.dict.match_prefix(remaining_text) {
-                // 如果在词典中找到匹配，则将匹配到的词作为一个片段
-                segments.push(matched_key);
-                start += matched_key.len();
+        max_match_tagged(self.dict.as_ref(), text)
+            .into_iter()
+            .map(|(seg, _)| seg)
+            .collect()
+    }
+}
+
+/// 在正向最大匹配的基础上，用字符级 BMES HMM 对单字回退片段做 Viterbi 重新切分，
+/// 以减少未登录词被拆成孤立单字的情况。
+pub struct HmmSegmentation {
+    dict: Arc<dyn Dictionary>,
+    model: &'static HmmModel,
+}
+
+impl HmmSegmentation {
+    /// 使用指定的词典与 HMM 模型创建一个新的 `HmmSegmentation` 实例
+    pub fn new(dict: Arc<dyn Dictionary>, model: &'static HmmModel) -> Self {
+        Self { dict, model }
+    }
+}
+
+impl Segmentation for HmmSegmentation {
+    fn segment<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let tagged = max_match_tagged(self.dict.as_ref(), text);
+        let mut segments = Vec::with_capacity(tagged.len());
+        let mut i = 0;
+
+        while i < tagged.len() {
+            if tagged[i].1 {
+                // 收集一段连续的单字回退，作为一个待重新切分的片段
+                let mut j = i + 1;
+                while j < tagged.len() && tagged[j].1 {
+                    j += 1;
+                }
+                let run_start = tagged[i].0.as_ptr() as usize - text.as_ptr() as usize;
+                let run_len: usize = tagged[i..j].iter().map(|(seg, _)| seg.len()).sum();
+                let run = &text[run_start..run_start + run_len];
+                segments.extend(hmm::resegment(self.model, run));
+                i = j;
             } else {
-                // 如果没有找到匹配，则安全地分割出当前位置的第一个字符
-                let ch_end = remaining_text
-                    .char_indices()
-                    .nth(1)
-                    .map_or(remaining_text.len(), |(idx, _)| idx);
-                segments.push(&remaining_text[..ch_end]);
-                start += ch_end;
+                segments.push(tagged[i].0);
+                i += 1;
             }
         }
+
         segments
     }
 }
 
+/// 同时执行正向与反向最大匹配，并按经典启发式规则选出更优的切分
+pub struct BiMaxMatchSegmentation {
+    dict: Arc<dyn Dictionary>,
+}
+
+impl BiMaxMatchSegmentation {
+    /// 使用指定的词典创建一个新的 `BiMaxMatchSegmentation` 实例
+    pub fn new(dict: Arc<dyn Dictionary>) -> Self {
+        Self { dict }
+    }
+}
+
+impl Segmentation for BiMaxMatchSegmentation {
+    fn segment<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let forward = max_match_tagged(self.dict.as_ref(), text)
+            .into_iter()
+            .map(|(seg, _)| seg)
+            .collect();
+        let reverse = bimm::segment_reverse(self.dict.as_ref(), text);
+
+        bimm::pick_better(forward, reverse)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,14 +219,14 @@ mod tests {
     }
 
     impl Dictionary for MockDict {
-        fn match_prefix<'a, 'b>(&'a self, word: &'b str) -> Option<(&'b str, &'a [Arc<str>])> {
+        fn match_prefix<'b>(&self, word: &'b str) -> Option<(&'b str, Arc<[Arc<str>]>)> {
             let mut longest_match_len = 0;
-            let mut result: Option<(&'b str, &'a [Arc<str>])> = None;
+            let mut result: Option<(&'b str, Arc<[Arc<str>]>)> = None;
 
             for (key, values) in &self.entries {
                 if word.starts_with(key) && key.len() > longest_match_len {
                     longest_match_len = key.len();
-                    result = Some((&word[..key.len()], values.as_slice()));
+                    result = Some((&word[..key.len()], values.as_slice().into()));
                 }
             }
             result
@@ -169,4 +270,39 @@ mod tests {
         let segments4 = segmenter.segment(text4);
         assert_eq!(segments4, Vec::<&str>::new());
     }
+
+    #[test]
+    fn test_hmm_segmentation_falls_back_to_viterbi_on_unknown_runs() {
+        let mut dict = MockDict::default();
+        dict.add_entry("的", "的");
+        let dict_arc: Arc<dyn Dictionary> = Arc::new(dict);
+
+        let segmenter = HmmSegmentation::new(dict_arc, HmmModel::embedded());
+
+        // “的”总能被词典直接命中，其余字符都是未登录词，交给 Viterbi 重新切分
+        let segments = segmenter.segment("的未登录词的测试");
+        // Viterbi 的切分结果取决于种子概率表，这里只验证：
+        // 1. 词典命中的字符被原样保留；2. 总字符数不变；3. 没有产生越界片段
+        assert_eq!(segments.iter().map(|s| s.chars().count()).sum::<usize>(), 8);
+        assert!(segments.contains(&"的"));
+    }
+
+    #[test]
+    fn test_bimm_resolves_forward_backward_ambiguity() {
+        // 经典的歧义切分样例：正向最大匹配会切出“研究生/命/起源”，
+        // 反向最大匹配则能切出更合理的“研究/生命/起源”（词数相同，但单字词更少）。
+        let mut dict = MockDict::default();
+        dict.add_entry("研究", "研究");
+        dict.add_entry("研究生", "研究生");
+        dict.add_entry("生命", "生命");
+        dict.add_entry("起源", "起源");
+        let dict_arc: Arc<dyn Dictionary> = Arc::new(dict);
+
+        let segmenter = BiMaxMatchSegmentation::new(dict_arc.clone());
+        let segments = segmenter.segment("研究生命起源");
+        assert_eq!(segments, vec!["研究", "生命", "起源"]);
+
+        let forward = MaxMatchSegmentation::new(dict_arc).segment("研究生命起源");
+        assert_eq!(forward, vec!["研究生", "命", "起源"]);
+    }
 }